@@ -153,7 +153,7 @@ fn main() -> Result<(), Box<dyn Error>> {
 		1 => {
 			println!("Testing row operations not using pivot pairs ...");
 			let now = Instant::now();
-			let (rowoper, indexing_row) = decomp_row(&matrix_row, &mut keys_dim0);
+			let (rowoper, indexing_row) = decomp_row(&matrix_row, &mut keys_dim0).unwrap();
 			println!("decomp_row runs {} nanos and outputs {} pairs.", now.elapsed().as_nanos(), indexing_row.index_2_majkey.len());
 
 			let mut index_to_majkey = indexing_row.index_2_majkey.clone();
@@ -165,7 +165,7 @@ fn main() -> Result<(), Box<dyn Error>> {
 		2 => {
 			println!("Testing row operations using pivot pairs ...");
 			let now = Instant::now();
-			let (rowoper2, indexing_row_2) = decomp_row_use_pairs(&matrix_row, &mut maj_to_reduce_vec.clone(), &mut min_to_reduce_set);
+			let (rowoper2, indexing_row_2) = decomp_row_use_pairs(&matrix_row, &mut maj_to_reduce_vec.clone(), &mut min_to_reduce_set).unwrap();
 			println!("decomp_row_use_pairs runs {} nanos and outputs {} pairs.", now.elapsed().as_nanos(), indexing_row_2.index_2_majkey.len());
 
 			let mut index_to_majkey = indexing_row_2.index_2_majkey.clone();
@@ -177,7 +177,7 @@ fn main() -> Result<(), Box<dyn Error>> {
 		3 => {
 			println!("Testing column operations not using pivot pairs ...");
 			let now = Instant::now();
-			let (coloper, indexing_col) = decomp_col(&matrix_col, &mut keys_dim1);
+			let (coloper, indexing_col) = decomp_col(&matrix_col, &mut keys_dim1).unwrap();
 			println!("decomp_col runs {} nanos and outputs {} pairs.", now.elapsed().as_nanos(), indexing_col.index_2_majkey.len());
 
 			let mut index_to_majkey = indexing_col.index_2_majkey.clone();
@@ -190,7 +190,7 @@ fn main() -> Result<(), Box<dyn Error>> {
 		4 => {
 			println!("Testing column operations using pivot pairs ...");
 			let now = Instant::now();
-			let (coloper2, indexing_col_2) = decomp_col_use_pairs(&matrix_col, &mut min_to_reduce_vec.clone(), &mut maj_to_reduce_set);
+			let (coloper2, indexing_col_2) = decomp_col_use_pairs(&matrix_col, &mut min_to_reduce_vec.clone(), &mut maj_to_reduce_set).unwrap();
 			println!("decomp_col_use_pairs runs {} nanos and outputs {} pairs.", now.elapsed().as_nanos(), indexing_col_2.index_2_majkey.len());
 
 			let mut index_to_majkey = indexing_col_2.index_2_majkey.clone();
@@ -0,0 +1,81 @@
+use exhact::chx::factor_chain_complex_multiprime;
+use exhact::matrix::{RingSpec, RingMetadata, MajorDimension};
+use exhact::clique::CliqueComplex;
+
+use ndarray::Array2;
+use ndarray_npy::read_npy;
+
+use std::error::Error;
+use std::env;
+
+use math::round;
+use ordered_float::OrderedFloat;
+
+use std::time::Instant;
+
+fn main() -> Result<(), Box<dyn Error>> {
+///////////////////////////////////////////////////////////////////////////////////////////////////
+// Accessing commond line arguments
+
+	let args: Vec<String> = env::args().collect();
+	let mut data_file_name = args[1].clone();
+	let dim: usize = args[2].trim().parse().expect("Please type a number!");
+	let primes: Vec<usize> = args[3].split(',').map(|p| p.trim().parse().expect("Please type a comma-separated list of primes!")).collect();
+	data_file_name.push_str("/dismat.npy");
+	println!("Dissimilarity matrix data is in file: {:?}", data_file_name);
+	let arr: Array2<f64> = read_npy(data_file_name).unwrap();
+/////////////////// Read dissimilarity matrix data into a Vec<Vec<FilVal>>
+
+	let mut dis_mat = Vec::new();
+	let mut min_max = OrderedFloat(0.0);
+	for row in arr.outer_iter() {
+		let mut vector = Vec::new();
+		let mut max = OrderedFloat(0.0);
+		for entry in row.iter() {
+			let rounded_entry = round::floor(*entry, 15);
+			vector.push(OrderedFloat(rounded_entry));
+			if OrderedFloat(rounded_entry) > max { max = OrderedFloat(rounded_entry); }
+		}
+		dis_mat.push(vector);
+		if max < min_max || min_max == OrderedFloat(0.0) { min_max = max; }
+	}
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+	let build_complex = |prime: usize| CliqueComplex {
+		dissimilarity_matrix: dis_mat.clone(),
+		dissimilarity_value_max: min_max,
+		safe_homology_degrees_to_build_boundaries: (1..(dim+2)).collect(),
+		major_dimension: MajorDimension::Row,
+		ringmetadata: RingMetadata{
+			ringspec: RingSpec::Modulus(prime),
+			identity_additive: 0i64,
+			identity_multiplicative: 1i64,
+		},
+		simplex_count: Vec::new()
+	};
+
+	println!("Factoring over the fields of order {:?} ...", primes);
+	let now = Instant::now();
+	let multiprime = factor_chain_complex_multiprime(&primes, build_complex, dim).unwrap();
+	println!("Runs {} seconds", now.elapsed().as_secs());
+
+	for (prime, barcodes) in multiprime.primes.iter().zip(multiprime.barcodes.iter()) {
+		let lengths: Vec<usize> = barcodes.iter().map(|barcode| barcode.len()).collect();
+		println!("Number of bars in dimensions 0..={} over Z/{}Z: {:?}", dim, prime, lengths);
+	}
+
+	let candidates = multiprime.torsion_candidates();
+	if candidates.is_empty() {
+		println!("The barcodes agree over every field; no torsion detected.");
+	} else {
+		println!("Bars whose multiplicity changes between fields (dimension, birth, death, multiplicities):");
+		for candidate in candidates.iter() {
+			println!("{},{},{},{:?}", candidate.h_degree, candidate.bar.0, candidate.bar.1, candidate.multiplicities);
+		}
+	}
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+    Ok(())
+}
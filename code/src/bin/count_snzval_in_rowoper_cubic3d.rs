@@ -130,9 +130,9 @@ fn main() -> Result<(), Box<dyn Error>> {
 	println!("Num of all cols: {}", keys_min.len());
 	println!("Num of paired rows: {}", maj_to_reduce_vec.len());
 
-	let (_, _, counter_full) = decomp_row_with_snzval_counter(&matrix_row, &mut keys_maj);
+	let (_, _, counter_full) = decomp_row_with_snzval_counter(&matrix_row, &mut keys_maj).unwrap();
 	println!("Num of snzval in full row operation matrix: {}", counter_full);
-	let (_, _, counter_pair) = decomp_row_with_snzval_counter(&matrix_row, &mut maj_to_reduce_vec);
+	let (_, _, counter_pair) = decomp_row_with_snzval_counter(&matrix_row, &mut maj_to_reduce_vec).unwrap();
 	println!("Num of snzval in the pivot block of row operation matrix: {}", counter_pair);
 
 ///////////////////////////////////////////////////////////////////////////////////////////////////
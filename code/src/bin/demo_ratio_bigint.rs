@@ -0,0 +1,79 @@
+use exhact::matrix::{RingSpec, RingMetadata, MajorDimension};
+use exhact::clique::CliqueComplex;
+use exhact::chx::factor_chain_complex;
+use num::{BigInt, BigRational};
+
+
+fn main() {
+
+    // ----------------------------------------------------------------------------------
+    // Set maximum threshold values for homology dimension and dissimilarity
+    // ----------------------------------------------------------------------------------
+    let dim = 2;
+    let maxdis = 6;
+
+
+    // ----------------------------------------------------------------------------------
+    // Build a "dissimilarity matrix" for 12 points spaced evenly around a circle;
+    // the distance between two points is the number of steps between them
+    // ----------------------------------------------------------------------------------
+    let numpoints: i64 = 12;
+    let dismat: Vec<Vec<i64>> = (0..numpoints).map(|i| {
+        (0..numpoints).map(|j| {
+            let steps = (i - j).abs();
+            std::cmp::min(steps, numpoints - steps)
+        }).collect()
+    }).collect();
+
+
+    // ----------------------------------------------------------------------------------
+    // Factor the complex with exact rational coefficients (backed by big integers)
+    // ----------------------------------------------------------------------------------
+    let ringmetadata = RingMetadata{
+        ringspec: RingSpec::Rational,
+        identity_additive: BigRational::from_integer(BigInt::from(0)),
+        identity_multiplicative: BigRational::from_integer(BigInt::from(1)),
+    };
+    let chx_rational = CliqueComplex {
+        dissimilarity_matrix: dismat.clone(),
+        dissimilarity_value_max: maxdis,
+        safe_homology_degrees_to_build_boundaries: (1..dim+1).collect(),
+        major_dimension: MajorDimension::Row,
+        ringmetadata,
+        simplex_count: Vec::new()
+    };
+    let factored_rational = factor_chain_complex(&chx_rational, dim+1).unwrap();
+
+
+    // ----------------------------------------------------------------------------------
+    // Factor the same complex over a large prime field, for comparison
+    // ----------------------------------------------------------------------------------
+    let ringmetadata = RingMetadata{
+        ringspec: RingSpec::Modulus(2147483647),
+        identity_additive: 0i64,
+        identity_multiplicative: 1i64,
+    };
+    let chx_modp = CliqueComplex {
+        dissimilarity_matrix: dismat,
+        dissimilarity_value_max: maxdis,
+        safe_homology_degrees_to_build_boundaries: (1..dim+1).collect(),
+        major_dimension: MajorDimension::Row,
+        ringmetadata,
+        simplex_count: Vec::new()
+    };
+    let factored_modp = factor_chain_complex(&chx_modp, dim+1).unwrap();
+
+
+    // ----------------------------------------------------------------------------------
+    // Print the barcodes and check that they agree
+    // ----------------------------------------------------------------------------------
+    for i in 0..dim+1 {
+        let mut barcode_rational = factored_rational.barcode(i);
+        let mut barcode_modp = factored_modp.barcode(i);
+        barcode_rational.sort();
+        barcode_modp.sort();
+        println!("Barcode in dimension {} (rational coefficients): {:?}", i, barcode_rational);
+        assert_eq!(barcode_rational, barcode_modp);
+    }
+    println!("Rational and mod-p barcodes agree.");
+}
@@ -146,14 +146,14 @@ fn main() {
     // ----------------------------------------------------------------------------------
     // Factor the complex 
     // ----------------------------------------------------------------------------------
-    let factored_complex = exhact::chx::factor_chain_complex(&chx, dim+1);
+    let factored_complex = exhact::chx::factor_chain_complex(&chx, dim+1).unwrap();
 
 
     // ----------------------------------------------------------------------------------
     // Read barcodes / check for correctness
     // ----------------------------------------------------------------------------------
     // predefine the set of correct solutions
-    let correct_barcodes = vec![    vec![ (0, 2), (0, 1), (0, 1), (0, 1) ], // dimension 0
+    let correct_barcodes = vec![    vec![ (0, 0), (0, 1), (0, 1), (0, 1) ], // dimension 0; an infinite bar born at b is written (b, b)
                                     vec![ (1, 2) ],                         // dimension 1
                                     vec![],                                 // dimension 2
                                     vec![]                                  // dimension 3
@@ -207,5 +207,14 @@ fn main() {
     // Get the birth and death filtraitons of a chain 
     //    - here "chain" is formalized as a hashmap mapping keys to coefficients
     // ----------------------------------------------------------------------------------
-    // this part is not implemented yet.
+    // the square 0-1-2-3 is a cycle that appears at filtration 1 and fills in at 2, when the diagonals appear
+    let mut chain = std::collections::HashMap::new();
+    chain.insert( Simplex{ filvalue: 1, vertices: vec![0, 1] }, Ratio::from_integer(1) );
+    chain.insert( Simplex{ filvalue: 1, vertices: vec![1, 2] }, Ratio::from_integer(1) );
+    chain.insert( Simplex{ filvalue: 1, vertices: vec![2, 3] }, Ratio::from_integer(1) );
+    chain.insert( Simplex{ filvalue: 1, vertices: vec![0, 3] }, Ratio::from_integer(-1) );
+
+    let birth_death = factored_complex.chain_birth_death(1, &chain);
+    println!("Birth and death of the chain: {:?}", birth_death);
+    std::assert_eq!( birth_death, Some( (1, Some(2)) ) );
 }
@@ -153,14 +153,14 @@ fn main() {
     // ----------------------------------------------------------------------------------
     // Factor the complex
     // ----------------------------------------------------------------------------------
-    let factored_complex = exhact::chx::factor_chain_complex(&chx, dim+1);
+    let factored_complex = exhact::chx::factor_chain_complex(&chx, dim+1).unwrap();
 
 
     // ----------------------------------------------------------------------------------
     // Read barcodes / check for correctness
     // ----------------------------------------------------------------------------------
     // predefine the set of correct solutions
-    let correct_barcodes = vec![    vec![ (0, 2), (0, 1), (0, 1), (0, 1) ], // dimension 0
+    let correct_barcodes = vec![    vec![ (0, 0), (0, 1), (0, 1), (0, 1) ], // dimension 0; an infinite bar born at b is written (b, b)
                                     vec![ (1, 2) ],                         // dimension 1
                                     vec![],                                 // dimension 2
                                     vec![]                                  // dimension 3
@@ -210,11 +210,6 @@ fn main() {
     std::assert_eq!( basis_vec_iter.eq( correct_val.iter().cloned() ) , true);
 
 
-    // ----------------------------------------------------------------------------------
-    // Get the birth and death filtraitons of a chain
-    //    - here "chain" is formalized as a hashmap mapping keys to coefficients
-    // ----------------------------------------------------------------------------------
-    // this part is not implemented yet.
 
 
 
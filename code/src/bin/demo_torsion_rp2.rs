@@ -0,0 +1,70 @@
+use exhact::matrix::{RingSpec, RingMetadata, MajorDimension};
+use exhact::clique::CliqueComplex;
+use exhact::decomp_pid::{barcode_pid, TorsionBar};
+
+
+fn main() {
+
+    // ----------------------------------------------------------------------------------
+    // The 6-vertex triangulation of the real projective plane
+    // ----------------------------------------------------------------------------------
+    let triangles: Vec<Vec<usize>> = vec![  vec![0, 1, 3], vec![0, 1, 5], vec![0, 2, 4], vec![0, 2, 5],
+                                            vec![0, 3, 4], vec![1, 2, 3], vec![1, 2, 4], vec![1, 4, 5],
+                                            vec![2, 3, 5], vec![3, 4, 5] ];
+
+
+    // ----------------------------------------------------------------------------------
+    // Its barycentric subdivision is a clique complex: the vertices are the faces of the
+    // triangulation, and two faces are adjacent if one contains the other.  Encode this as
+    // a dissimilarity matrix with value 1 for adjacent faces and 2 otherwise.
+    // ----------------------------------------------------------------------------------
+    let mut faces: Vec<Vec<usize>> = Vec::new();
+    for triangle in triangles.iter() {
+        for i in 0..3 {
+            let vertex = vec![triangle[i]];
+            let edge: Vec<usize> = triangle.iter().cloned().filter(|v| *v != triangle[i]).collect();
+            if !faces.contains(&vertex) { faces.push(vertex); }
+            if !faces.contains(&edge) { faces.push(edge); }
+        }
+        faces.push(triangle.clone());
+    }
+    let is_face_of = |a: &Vec<usize>, b: &Vec<usize>| a.iter().all(|v| b.contains(v));
+    let dismat: Vec<Vec<i64>> = faces.iter().map(|a| {
+        faces.iter().map(|b| {
+            if a == b { 0 } else if is_face_of(a, b) || is_face_of(b, a) { 1 } else { 2 }
+        }).collect()
+    }).collect();
+
+
+    // ----------------------------------------------------------------------------------
+    // Build the clique complex with integer coefficients
+    // ----------------------------------------------------------------------------------
+    let dim = 2;
+    let chx = CliqueComplex {
+        dissimilarity_matrix: dismat,
+        dissimilarity_value_max: 1,
+        safe_homology_degrees_to_build_boundaries: (1..dim+2).collect(),
+        major_dimension: MajorDimension::Row,
+        ringmetadata: RingMetadata{
+            ringspec: RingSpec::Integer,
+            identity_additive: 0i64,
+            identity_multiplicative: 1i64,
+        },
+        simplex_count: Vec::new()
+    };
+
+
+    // ----------------------------------------------------------------------------------
+    // Compute barcodes over the integers; once every face has appeared,
+    // H_0 = Z, H_1 = Z/2, and H_2 = 0
+    // ----------------------------------------------------------------------------------
+    for i in 1..dim+1 {
+        println!("Barcode in dimension {} (integer coefficients): {:?}", i, barcode_pid(&chx, i));
+    }
+
+    let infinite_bars_dim0: Vec<_> = barcode_pid(&chx, 0).into_iter().filter(|bar| bar.death.is_none()).collect();
+    assert_eq!( infinite_bars_dim0, vec![ TorsionBar{ birth: 0, death: None, torsion: vec![] } ] );
+    assert_eq!( barcode_pid(&chx, 1), vec![ TorsionBar{ birth: 1, death: None, torsion: vec![(1, 2)] } ] );
+    assert_eq!( barcode_pid(&chx, 2), vec![] );
+    println!("Found the Z/2 torsion of the projective plane.");
+}
@@ -15,16 +15,20 @@ fn main() {
     // 0 0 0 0 0
     // 0 5 0 0 0
 
-    let matrix: CSM<usize, usize> = CSM {
-        ringmetadata,
-        nummaj: 3,
-        majdim: MajorDimension::Row,
-        majptr: vec![0, 4, 4, 5],
-        minind: vec![0, 1, 2, 3, 1],
-        snzval: vec![1, 2, 3, 4, 5]
-    };
-
-    let trans: CSM<usize, usize> = transpose(4, &matrix);
+    let mut matrix: CSM<usize, u64> = CSM::new(MajorDimension::Row, ringmetadata);
+    matrix.nummaj = 3;
+    matrix.majptr = vec![0, 4, 4, 5];
+    matrix.minind = vec![0, 1, 2, 3, 1];
+    matrix.snzval = vec![1, 2, 3, 4, 5];
+
+    // modulo 2, the coefficients 2 and 4 are zero and 3 and 5 are not reduced; validate() reports both
+    if let Err(errors) = matrix.validate() {
+        for error in errors.iter() {
+            println!("{:?}", error);
+        }
+    }
+
+    let trans: CSM<usize, u64> = transpose(4, &matrix);
     trans.print();
 ///////////////////////////////////////////////////////////////////////////////////////////////////
 
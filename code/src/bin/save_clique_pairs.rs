@@ -77,7 +77,7 @@ fn main() -> Result<(), Box<dyn Error>> {
 
 	println!("Testing ...");
     let now = Instant::now();
-    let blocks = factor_chain_complex(&chx, dim+1);
+    let blocks = factor_chain_complex(&chx, dim+1).unwrap();
     println!("Runs {} seconds", now.elapsed().as_secs());
     println!("Done!");
 
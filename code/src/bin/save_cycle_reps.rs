@@ -79,7 +79,7 @@ fn main() -> Result<(), Box<dyn Error>> {
 
 	println!("Caculating barcodes ...");
     let now = Instant::now();
-    let blocks = factor_chain_complex(&chx, dim+1);
+    let blocks = factor_chain_complex(&chx, dim+1).unwrap();
     println!("Runs {} seconds", now.elapsed().as_secs());
     println!("Done!");
 
@@ -137,8 +137,9 @@ fn main() -> Result<(), Box<dyn Error>> {
 			let mut tmp = String::from("Cycle representative combination:\nSimplicies Coefficients") + "\n";
 			file.write(tmp.to_string().as_bytes()).unwrap();
 
-			let hash = blocks.get_matched_basis_vector(dim, &key);
-			for (simp, coef) in hash.iter(){
+			let mut rep: Vec<_> = blocks.get_matched_basis_vector(dim, &key).into_iter().collect();
+			rep.sort_by(|x, y| x.0.cmp(&y.0));
+			for (simp, coef) in rep.iter(){
 				let mut tmp = serde_json::to_string(&simp.vertices).unwrap();
 				tmp += " ";
 				tmp += &serde_json::to_string(&coef).unwrap();
@@ -166,8 +167,9 @@ fn main() -> Result<(), Box<dyn Error>> {
 			let mut tmp = String::from("Cycle representative:") + "\n";
 			file.write(tmp.to_string().as_bytes()).unwrap();
 
-			let hash = blocks.get_matched_basis_vector(dim, &key);
-			for (simp, coef) in hash.iter(){
+			let mut rep: Vec<_> = blocks.get_matched_basis_vector(dim, &key).into_iter().collect();
+			rep.sort_by(|x, y| x.0.cmp(&y.0));
+			for (simp, coef) in rep.iter(){
 				let mut tmp = serde_json::to_string(&simp.vertices).unwrap();
 				tmp += " ";
 				tmp += &serde_json::to_string(&coef).unwrap();
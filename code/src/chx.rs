@@ -81,13 +81,14 @@ Some information (e.g. persistence diagrams, cycle representatives) won't be ava
 use std::hash::Hash;
 use std::collections::HashMap;
 use std::marker::PhantomData;
+use std::sync::OnceLock;
 use std::fmt::Debug;
-use std::ops::{Add, Neg, AddAssign, Mul};
 
-use crate::matrix::{MajorDimension, SmOracle, InvMod};
-use crate::csm::CSM;
-use crate::solver::{multiply_hash_smoracle, triangular_solver_version_2};
+use crate::matrix::{MajorDimension, SmOracle, RingElement, Semiring, Transposed, DecompError};
+use crate::csm::{CSM, DualCsm};
+use crate::solver::{add_assign_hash, multiply_hash_smoracle, triangular_solve, Triangle, SolveSide};
 use crate::decomp_row::decomp_row;
+use crate::umatch::UMatch;
 
 // ================================================================================================
 // FILE DESCRIPTION
@@ -404,6 +405,24 @@ impl<MinKey: Hash + Eq, MajKey: Hash + Eq> Indexing<MinKey, MajKey>{
 
 }
 
+/// For each pivot index recorded in `indexing`, its position in `indexing.ordered_minind`
+fn pivot_positions<MinKey: Hash + Eq, MajKey: Hash + Eq>(indexing: &Indexing<MinKey, MajKey>) -> Vec<usize> {
+    let mut position = vec![0; indexing.ordered_minind.len()];
+    for (pos, index) in indexing.ordered_minind.iter().enumerate() {
+        position[*index] = pos;
+    }
+    position
+}
+
+/// The result of [`FactoredComplexBlockCsm::boundary_test`](FactoredComplexBlockCsm::boundary_test)
+#[derive(Clone, Debug, PartialEq)]
+pub struct BoundaryTest<MatrixIndexKey: Eq + Hash, SnzVal> {
+    /// True if the boundary of the chain is zero
+    pub is_cycle: bool,
+    /// A chain in the sublevel set whose boundary is the given chain, if there is one
+    pub bounding_chain: Option<HashMap<MatrixIndexKey, SnzVal>>,
+}
+
 /// A factored chain complex with change-of-basis matrices
 ///
 /// See also [Factoring](crate::chx) in the documentation for the `chx` module.
@@ -414,14 +433,16 @@ OriginalChx: ChainComplex<MatrixIndexKey, SnzVal, Filtration>
 {
     pub phantom: PhantomData<Filtration>,
     pub original_complex: &'a OriginalChx,   // Reference to the original complex
-    pub dim_rowoper: Vec<CSM<usize, SnzVal>>,
+    pub dim_rowoper: Vec<DualCsm<usize, SnzVal>>,  // row operation matrices, with a cached column index
     pub dim_indexing: Vec<Indexing<MatrixIndexKey, MatrixIndexKey>>,
+    dim_pivot_position: Vec<OnceLock<Vec<usize>>>,  // for each pivot index, its position in ordered_minind (built on demand)
+    dim_pivot_values: Vec<OnceLock<Vec<SnzVal>>>,   // the pivot values of the U-match in each degree (built on demand)
 }
 /// Methods of FactoredComplexBlockCsm struct
 impl<'a, MatrixIndexKey, SnzVal, Filtration, OriginalChx> FactoredComplexBlockCsm<'a, MatrixIndexKey, SnzVal, Filtration, OriginalChx> where
 Filtration: PartialOrd + Clone,
 MatrixIndexKey: PartialEq + Eq + Ord + Hash + Clone + Debug,
-SnzVal: Clone + PartialEq + Neg<Output=SnzVal> + AddAssign + Mul<Output = SnzVal> + InvMod<Output = SnzVal> + Debug,
+SnzVal: RingElement + Debug,
 OriginalChx: ChainComplex<MatrixIndexKey, SnzVal, Filtration>
 {
     /// Returns the persistence barcode of given dimension
@@ -455,6 +476,181 @@ OriginalChx: ChainComplex<MatrixIndexKey, SnzVal, Filtration>
         return barcode;
     }
 
+    /// Decide whether a chain is a cycle, and whether it is a boundary in the sublevel set at a given filtration value
+    ///
+    /// If the chain is a boundary by time `filtration`, the result also contains a bounding chain, made of simplices
+    /// (or other cells) of degree `h_degree + 1` that appear no later than `filtration`.  A chain that contains a cell
+    /// appearing after `filtration` is not a boundary at that time (it is not even a chain in the sublevel set).
+    /// The complex must have been factored in degree `h_degree + 1`, that is, with `max_homology_degree > h_degree`.
+    ///
+    /// ```
+    /// use exhact::matrix::{RingSpec, RingMetadata, MajorDimension};
+    /// use exhact::clique::{CliqueComplex, Simplex};
+    /// use exhact::chx::factor_chain_complex;
+    /// use std::collections::HashMap;
+    ///
+    /// // 8 points evenly spaced around a circle; the distance between two points is the number of steps between them
+    /// let dismat: Vec<Vec<i64>> = (0..8).map(|i: i64| (0..8).map(|j: i64| std::cmp::min((i-j).abs(), 8-(i-j).abs())).collect()).collect();
+    /// let chx = CliqueComplex {
+    ///     dissimilarity_matrix: dismat,
+    ///     dissimilarity_value_max: 4,
+    ///     safe_homology_degrees_to_build_boundaries: vec![1, 2],
+    ///     major_dimension: MajorDimension::Row,
+    ///     ringmetadata: RingMetadata{ ringspec: RingSpec::Modulus(3), identity_additive: 0i64, identity_multiplicative: 1i64 },
+    ///     simplex_count: Vec::new()
+    /// };
+    /// let factored = factor_chain_complex(&chx, 2).unwrap();
+    ///
+    /// // the loop that goes once around the circle
+    /// let mut chain = HashMap::new();
+    /// for i in 0..8 {
+    ///     let (a, b) = if i < 7 { (i, i+1) } else { (0, 7) };
+    ///     chain.insert(Simplex{ filvalue: 1, vertices: vec![a, b] }, if i < 7 { 1 } else { -1 });
+    /// }
+    ///
+    /// // the loop is a cycle, but it only becomes a boundary once the circle fills in
+    /// let test = factored.boundary_test(1, &chain, 1);
+    /// assert!(test.is_cycle && test.bounding_chain.is_none());
+    /// let test = factored.boundary_test(1, &chain, 3);
+    /// assert!(test.is_cycle && test.bounding_chain.is_some());
+    /// ```
+    ///
+    /// # Parameters
+    /// - `h_degree`: the degree of the chain
+    /// - `chain`: the chain, represented as a hash map from cells of degree `h_degree` to coefficients
+    /// - `filtration`: the filtration value at which to test
+    pub fn boundary_test(
+        &self,
+        h_degree:           usize,
+        chain:              &HashMap<MatrixIndexKey, SnzVal>,
+        filtration:         Filtration
+    ) -> BoundaryTest<MatrixIndexKey, SnzVal>
+    {
+        assert!(h_degree + 1 < self.dim_rowoper.len(), "the complex has not been factored in degree {}", h_degree + 1);
+
+        let is_cycle = self.is_cycle(chain);
+        let in_sublevel_set = |key: &MatrixIndexKey| self.original_complex.key_2_filtration(key) <= filtration;
+        if !is_cycle || !chain.keys().all(in_sublevel_set) {
+            return BoundaryTest{ is_cycle, bounding_chain: None };
+        }
+
+        let bounding_chain = self.bounding_chain(h_degree, chain).filter(|solution| solution.keys().all(in_sublevel_set));
+        BoundaryTest{ is_cycle, bounding_chain }
+    }
+
+    /// Return the filtration value at which a cycle is born, and the filtration value at which its homology class dies
+    ///
+    /// The birth is the filtration value at which the last cell of the chain appears.  The death is the first
+    /// filtration value at which the chain is a boundary, or `None` if it never becomes one (an infinite bar).
+    /// Returns `None` if the chain is zero or is not a cycle.  As with [`boundary_test`](FactoredComplexBlockCsm::boundary_test),
+    /// the complex must have been factored in degree `h_degree + 1`.
+    ///
+    /// ```
+    /// use exhact::matrix::{RingSpec, RingMetadata, MajorDimension};
+    /// use exhact::clique::{CliqueComplex, Simplex};
+    /// use exhact::chx::factor_chain_complex;
+    /// use std::collections::HashMap;
+    ///
+    /// // 8 points evenly spaced around a circle; the distance between two points is the number of steps between them
+    /// let dismat: Vec<Vec<i64>> = (0..8).map(|i: i64| (0..8).map(|j: i64| std::cmp::min((i-j).abs(), 8-(i-j).abs())).collect()).collect();
+    /// let chx = CliqueComplex {
+    ///     dissimilarity_matrix: dismat,
+    ///     dissimilarity_value_max: 4,
+    ///     safe_homology_degrees_to_build_boundaries: vec![1, 2],
+    ///     major_dimension: MajorDimension::Row,
+    ///     ringmetadata: RingMetadata{ ringspec: RingSpec::Modulus(3), identity_additive: 0i64, identity_multiplicative: 1i64 },
+    ///     simplex_count: Vec::new()
+    /// };
+    /// let factored = factor_chain_complex(&chx, 2).unwrap();
+    ///
+    /// // the loop that goes once around the circle is born at 1 and dies when the circle fills in
+    /// let mut chain = HashMap::new();
+    /// for i in 0..8 {
+    ///     let (a, b) = if i < 7 { (i, i+1) } else { (0, 7) };
+    ///     chain.insert(Simplex{ filvalue: 1, vertices: vec![a, b] }, if i < 7 { 1 } else { -1 });
+    /// }
+    /// assert_eq!(factored.chain_birth_death(1, &chain), Some((1, Some(3))));
+    ///
+    /// // a single edge is not a cycle
+    /// let edge: HashMap<_, _> = vec![(Simplex{ filvalue: 1, vertices: vec![0, 1] }, 1)].into_iter().collect();
+    /// assert_eq!(factored.chain_birth_death(1, &edge), None);
+    /// ```
+    pub fn chain_birth_death(
+        &self,
+        h_degree:           usize,
+        chain:              &HashMap<MatrixIndexKey, SnzVal>
+    ) -> Option<(Filtration, Option<Filtration>)>
+    {
+        assert!(h_degree + 1 < self.dim_rowoper.len(), "the complex has not been factored in degree {}", h_degree + 1);
+
+        let matrix = self.original_complex.get_smoracle(MajorDimension::Row, ChxTransformKind::Boundary);
+        let ring = matrix.ring();
+        let support: Vec<&MatrixIndexKey> = chain.iter()
+            .filter(|(_, val)| !ring.is_0(&ring.simplify(val)))
+            .map(|(key, _)| key)
+            .collect();
+        if support.is_empty() || !self.is_cycle(chain) { return None; }
+
+        let birth = self.latest_filtration(support.into_iter())?;
+        let death = self.bounding_chain(h_degree, chain).map(|solution| {
+            match self.latest_filtration(solution.keys()) {
+                Some(latest) if latest > birth => latest,
+                _ => birth.clone(),
+            }
+        });
+        Some((birth, death))
+    }
+
+    /// True if the boundary of the chain is zero
+    fn is_cycle(&self, chain: &HashMap<MatrixIndexKey, SnzVal>) -> bool {
+        let matrix = self.original_complex.get_smoracle(
+            MajorDimension::Row,
+            ChxTransformKind::Boundary
+        );
+        let ring = matrix.ring();
+
+        // the boundary of the chain; the columns of the (row-major) boundary matrix are boundaries of cells
+        let mut boundary = HashMap::new();
+        for (key, val) in chain.iter() {
+            add_assign_hash(ring, &mut boundary, &mut matrix.min_hash(key), val);
+        }
+        boundary.values().all(|val| ring.is_0(&ring.simplify(val)))
+    }
+
+    /// A chain of degree `h_degree + 1` whose boundary is the given cycle, if there is one in the full complex
+    ///
+    /// The solution is computed from the matching decomposition, so its latest cell is as early as possible.
+    fn bounding_chain(&self, h_degree: usize, chain: &HashMap<MatrixIndexKey, SnzVal>) -> Option<HashMap<MatrixIndexKey, SnzVal>> {
+        let matrix = self.original_complex.get_smoracle(
+            MajorDimension::Row,
+            ChxTransformKind::Boundary
+        );
+        self.umatch(&matrix, h_degree+1).solve(chain)
+    }
+
+    /// The U-match of the (row-major) boundary matrix in degree `degree`; its pivot values are computed the first time it is requested
+    fn umatch<'b>(&'b self, matrix: &'b OriginalChx::Matrix, degree: usize) -> UMatch<'b, MatrixIndexKey, MatrixIndexKey, SnzVal, OriginalChx::Matrix, DualCsm<usize, SnzVal>> {
+        let rowoper = &self.dim_rowoper[degree];
+        let indexing = &self.dim_indexing[degree];
+        let pivot_values = self.dim_pivot_values[degree].get_or_init(|| UMatch::new(matrix, rowoper, indexing).pivot_values().to_vec());
+        UMatch::with_pivot_values(matrix, rowoper, indexing, pivot_values)
+    }
+
+    /// The latest filtration value of a collection of keys, or `None` if the collection is empty
+    fn latest_filtration<'b, I>(&self, keys: I) -> Option<Filtration> where
+    I: Iterator<Item = &'b MatrixIndexKey>,
+    MatrixIndexKey: 'b
+    {
+        let mut latest: Option<Filtration> = None;
+        for key in keys {
+            let filtration = self.original_complex.key_2_filtration(key);
+            if latest.as_ref().map_or(true, |current| filtration > *current) {
+                latest = Some(filtration);
+            }
+        }
+        latest
+    }
+
     /// Return matched basis vector of given original_basis at given dimension
     pub fn get_matched_basis_vector(
         &self,
@@ -467,76 +663,25 @@ OriginalChx: ChainComplex<MatrixIndexKey, SnzVal, Filtration>
             MajorDimension::Row,
             ChxTransformKind::Boundary
         );
+        let one = matrix.ring().identity_multiplicative.clone();
 
-        if self.dim_indexing[h_degree+1].majkey_2_index.contains_key(original_basis) {
+        if let Some(index) = self.dim_indexing[h_degree+1].majkey_2_index.get(original_basis) {
             let indexing = &self.dim_indexing[h_degree+1];
-            let index = indexing.majkey_2_index[original_basis];
-            let minkey = &indexing.index_2_minkey[index];
-
-            let order = &(0..indexing.index_2_majkey.len()).collect();
-            let rowoper = self.dim_rowoper[h_degree+1].change_major_dim(order);
-            let mut reduced = CSM::new(MajorDimension::Col, matrix.ring().clone());
-            let mut boundary = CSM::new(MajorDimension::Col, matrix.ring().clone());
-
-            let mut col_boundary = HashMap::new();
-            let mut col_reduced = HashMap::new();
-            let mut col_inverse = HashMap::new();
-            let mut bb = HashMap::new();
-            for minind in indexing.ordered_minind.iter() {
-                col_boundary.clear();
-                let minor_key = &indexing.index_2_minkey[*minind];
-                for (key, val) in matrix.min_itr(&minor_key) {
-                    boundary.push_snzval(key.clone(), val.clone());
-                    if indexing.majkey_2_index.contains_key(&key) {
-                        col_boundary.insert(indexing.majkey_2_index[&key], val);
-                    }
-                }
-                boundary.majptr.push(boundary.minind.len());
-                boundary.nummaj += 1;
-
-                col_reduced.clear();
-                col_reduced = multiply_hash_smoracle(&col_boundary, &rowoper);
-                reduced.append_maj(&mut col_reduced);
+            let minkey = &indexing.index_2_minkey[*index];
+            let bb = HashMap::from([(self.pivot_position(h_degree+1)[*index], one)]);
+            let col_inverse = self.solve_reduced_boundary(h_degree+1, Some(minkey), &bb);
 
-                if minor_key == minkey {
-                    bb.insert(*minind, matrix.ring().identity_multiplicative.clone());
-                    break;
-                }
+            // the corresponding combination of columns of the boundary matrix
+            let mut new_basis = HashMap::new();
+            for (pos, val) in col_inverse.iter() {
+                let minor_key = &indexing.index_2_minkey[indexing.ordered_minind[*pos]];
+                add_assign_hash(matrix.ring(), &mut new_basis, &mut matrix.min_hash(minor_key), val);
             }
-
-            col_inverse.clear();
-            col_inverse = triangular_solver_version_2(&reduced, &indexing.ordered_minind, &mut bb);
-            return multiply_hash_smoracle(&col_inverse, &boundary);
-        } else if self.dim_indexing[h_degree].minkey_2_index.contains_key(original_basis){
+            return new_basis;
+        } else if let Some(index) = self.dim_indexing[h_degree].minkey_2_index.get(original_basis) {
             let indexing = &self.dim_indexing[h_degree];
-            let order = &(0..indexing.index_2_majkey.len()).collect();
-            let rowoper = self.dim_rowoper[h_degree].change_major_dim(order);
-            let mut reduced = CSM::new(MajorDimension::Col, matrix.ring().clone());
-
-            let mut col_boundary = HashMap::new();
-            let mut col_reduced = HashMap::new();
-            let mut col_inverse = HashMap::new();
-            let mut bb = HashMap::new();
-            for minind in indexing.ordered_minind.iter() {
-                col_boundary.clear();
-                let minor_key = &indexing.index_2_minkey[*minind];
-                for (key, val) in matrix.min_itr(minor_key) {
-                    if indexing.majkey_2_index.contains_key(&key) {
-                        col_boundary.insert(indexing.majkey_2_index[&key], val);
-                    }
-                }
-                col_reduced.clear();
-                col_reduced = multiply_hash_smoracle(&col_boundary, &rowoper);
-                reduced.append_maj(&mut col_reduced);
-
-                if minor_key == original_basis {
-                    bb.insert(*minind, matrix.ring().identity_multiplicative.clone());
-                    break;
-                }
-            }
-
-            col_inverse.clear();
-            col_inverse = triangular_solver_version_2(&reduced, &indexing.ordered_minind, &mut bb);
+            let bb = HashMap::from([(self.pivot_position(h_degree)[*index], one)]);
+            let mut col_inverse = self.solve_reduced_boundary(h_degree, Some(original_basis), &bb);
 
             let mut new_basis = HashMap::new();
             for (ind, val) in col_inverse.drain(){
@@ -545,45 +690,60 @@ OriginalChx: ChainComplex<MatrixIndexKey, SnzVal, Filtration>
             }
             return new_basis;
         } else {
-            let indexing = &self.dim_indexing[h_degree];
-            let order = &(0..indexing.index_2_majkey.len()).collect();
-            let rowoper = self.dim_rowoper[h_degree].change_major_dim(order);
-            let mut reduced = CSM::new(MajorDimension::Col, matrix.ring().clone());
-
-            let mut col_boundary = HashMap::new();
-            let mut col_reduced = HashMap::new();
-            for minind in indexing.ordered_minind.iter() {
-                col_boundary.clear();
-                let minor_key = &indexing.index_2_minkey[*minind];
-                for (key, val) in matrix.min_itr(minor_key) {
-                    if indexing.majkey_2_index.contains_key(&key) {
-                        col_boundary.insert(indexing.majkey_2_index[&key], val);
-                    }
-                }
-                col_reduced.clear();
-                col_reduced = multiply_hash_smoracle(&col_boundary, &rowoper);
-                reduced.append_maj(&mut col_reduced);
-            }
+            // a key that is matched in neither degree is its own matched basis vector
+            let mut new_basis = HashMap::new();
+            new_basis.insert(original_basis.clone(), one);
+            return new_basis;
+        }
+    }
+
+    /// For each pivot index in degree `degree`, its position in `ordered_minind`; the table is built the first time it is requested
+    fn pivot_position(&self, degree: usize) -> &[usize] {
+        self.dim_pivot_position[degree].get_or_init(|| pivot_positions(&self.dim_indexing[degree]))
+    }
 
+    /// Solve `x R = b`, where `R` is the reduced boundary matrix of degree `degree` and `b` is `rhs`
+    ///
+    /// The rows and columns of `R`, and the keys of `rhs` and of the solution, are positions in `ordered_minind`, so that `R` is
+    /// upper triangular.  Only the columns up to and including the one of the minor key `last` are formed (all of them if `last` is `None`).
+    fn solve_reduced_boundary(
+        &self,
+        degree:             usize,
+        last:               Option<&MatrixIndexKey>,
+        rhs:                &HashMap<usize, SnzVal>
+    ) -> HashMap<usize, SnzVal>
+    {
+        let matrix = self.original_complex.get_smoracle(
+            MajorDimension::Row,
+            ChxTransformKind::Boundary
+        );
+        let indexing = &self.dim_indexing[degree];
+        let position = self.pivot_position(degree);
+        // the columns of the row operation matrix
+        let rowoper = Transposed::new(&self.dim_rowoper[degree]);
+        let mut reduced = CSM::new(MajorDimension::Col, matrix.ring().clone());
+
+        let mut col_boundary = HashMap::new();
+        for minind in indexing.ordered_minind.iter() {
             col_boundary.clear();
-            for (key, val) in matrix.min_itr(original_basis) {
-                if indexing.majkey_2_index.contains_key(&key) {
-                    col_boundary.insert(indexing.majkey_2_index[&key], val);
+            let minor_key = &indexing.index_2_minkey[*minind];
+            for (key, val) in matrix.min_itr(minor_key) {
+                if let Some(index) = indexing.majkey_2_index.get(&key) {
+                    col_boundary.insert(*index, val);
                 }
             }
+            // index rows by position, so that the reduced matrix is upper triangular
+            let mut col_reduced: HashMap<usize, SnzVal> = multiply_hash_smoracle(&col_boundary, &rowoper).into_iter()
+                .map(|(index, val)| (position[index], val))
+                .collect();
+            reduced.append_maj(&mut col_reduced);
 
-            let mut col_inverse = triangular_solver_version_2(&reduced, &indexing.ordered_minind, &mut col_boundary);
-            let mut new_basis = HashMap::new();
-            for (ind, val) in col_inverse.drain(){
-                let min_ind = indexing.ordered_minind[ind];
-                new_basis.insert(indexing.index_2_minkey[min_ind].clone(), val);
-            }
-            let mut new_basis = HashMap::new();
-            new_basis.insert(original_basis.clone(), matrix.ring().identity_multiplicative.clone());
-            return new_basis;
+            if Some(minor_key) == last { break; }
         }
-    }
 
+        triangular_solve(&reduced, Triangle::Upper, SolveSide::Right, false, rhs)
+            .expect("the reduced boundary matrix should be triangular with invertible pivots")
+    }
 }
 
 
@@ -593,15 +753,17 @@ OriginalChx: ChainComplex<MatrixIndexKey, SnzVal, Filtration>
 /// - `original_complex`: The chaincomplex instance based on which we want to compute barcodes
 /// - `max_homology_degree`: The max degree of which we want to compute barcodes
 /// # Returns
-/// an FactoredComplexBlockCsm struct which records decomposition information of each dimension
+/// an FactoredComplexBlockCsm struct which records decomposition information of each dimension, or
+/// [`DecompError::NotAField`](crate::matrix::DecompError::NotAField) if the coefficient ring is not a field
+/// (for integer coefficients, see [`barcode_pid`](crate::decomp_pid::barcode_pid))
 /// # See also
 /// [Factoring](crate::chx) in the documentation for the `chx` module.
 pub fn factor_chain_complex<'a, MatrixIndexKey, SnzVal, Filtration, OriginalChx>(
     original_complex:       &'a OriginalChx,
     max_homology_degree:    usize
-) -> FactoredComplexBlockCsm<'a, MatrixIndexKey, SnzVal, Filtration, OriginalChx> where
+) -> Result<FactoredComplexBlockCsm<'a, MatrixIndexKey, SnzVal, Filtration, OriginalChx>, DecompError> where
 MatrixIndexKey: PartialEq + Eq + Ord + Hash + Clone + Debug,
-SnzVal: Clone + PartialEq + Neg<Output=SnzVal> + AddAssign + Mul<Output = SnzVal> + InvMod<Output = SnzVal> + Debug + Add,
+SnzVal: RingElement + Debug,
 Filtration: Debug + PartialOrd,
 OriginalChx: ChainComplex<MatrixIndexKey, SnzVal, Filtration>
 {
@@ -613,8 +775,10 @@ OriginalChx: ChainComplex<MatrixIndexKey, SnzVal, Filtration>
     let mut blocks = FactoredComplexBlockCsm{
 		phantom: PhantomData,
 		original_complex: original_complex,
-		dim_rowoper: vec![CSM::new(MajorDimension::Row, matrix.ring().clone())],
-		dim_indexing: vec![Indexing::new()]
+		dim_rowoper: vec![DualCsm::new(CSM::new(MajorDimension::Row, matrix.ring().clone()))],
+		dim_indexing: vec![Indexing::new()],
+		dim_pivot_position: vec![OnceLock::new()],
+		dim_pivot_values: vec![OnceLock::new()]
 	};
 
 	let mut maj_to_reduce = Vec::new();
@@ -627,11 +791,171 @@ OriginalChx: ChainComplex<MatrixIndexKey, SnzVal, Filtration>
 			}
         }
         //println!("length of maj_to_reduce {}", maj_to_reduce.len());
-		let (rowoper, indexing) = decomp_row(&matrix, &mut maj_to_reduce);
+		let (rowoper, indexing) = decomp_row(&matrix, &mut maj_to_reduce)?;
         //println!("{}", indexing.index_2_majkey.len());
-		blocks.dim_rowoper.push(rowoper);
+		blocks.dim_rowoper.push(DualCsm::new(rowoper));
 		blocks.dim_indexing.push(indexing);
+		blocks.dim_pivot_position.push(OnceLock::new());
+		blocks.dim_pivot_values.push(OnceLock::new());
 	}
 
-    return blocks;
+    Ok(blocks)
+}
+
+/// Barcodes of a chain complex computed over several prime fields
+///
+/// Built by [`factor_chain_complex_multiprime`](factor_chain_complex_multiprime).
+#[derive(Clone, Debug)]
+pub struct MultiPrimeBarcodes<Filtration> {
+    /// The orders of the coefficient fields
+    pub primes: Vec<usize>,
+    /// `barcodes[i][h]` is the barcode in homology degree `h` with coefficients in Z/pZ, where `p = primes[i]`
+    pub barcodes: Vec<Vec<Vec<(Filtration, Filtration)>>>,
+}
+
+/// A bar whose multiplicity depends on the coefficient field
+///
+/// By the universal coefficient theorem such bars indicate torsion in integral homology: if a bar in degree `h` appears more often over Z/pZ than over the other fields, then integral homology has p-torsion in degree `h` or `h-1`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TorsionCandidate<Filtration> {
+    /// The homology degree of the bar
+    pub h_degree: usize,
+    /// The bar, as `(birth, death)`; infinite bars are written `(birth, birth)`, as in [`FactoredComplexBlockCsm::barcode`](FactoredComplexBlockCsm::barcode)
+    pub bar: (Filtration, Filtration),
+    /// The multiplicity of the bar over each field, in the same order as [`MultiPrimeBarcodes::primes`](MultiPrimeBarcodes)
+    pub multiplicities: Vec<usize>,
+}
+
+/// Methods of MultiPrimeBarcodes struct
+impl<Filtration> MultiPrimeBarcodes<Filtration> where
+Filtration: PartialOrd + Clone
+{
+    /// Returns the bars whose multiplicity is not the same over every field, sorted by degree and then by bar
+    ///
+    /// ```
+    /// use exhact::chx::{MultiPrimeBarcodes, TorsionCandidate};
+    ///
+    /// // the bar (1, 3) in degree 1 appears twice over Z/2Z but once over Z/3Z
+    /// let multiprime = MultiPrimeBarcodes{
+    ///     primes: vec![2, 3],
+    ///     barcodes: vec![ vec![ vec![(0, 0)], vec![(1, 3), (1, 3), (2, 4)] ],
+    ///                     vec![ vec![(0, 0)], vec![(1, 3), (2, 4)] ] ],
+    /// };
+    /// assert_eq!( multiprime.torsion_candidates(),
+    ///             vec![ TorsionCandidate{ h_degree: 1, bar: (1, 3), multiplicities: vec![2, 1] } ] );
+    /// ```
+    pub fn torsion_candidates(&self) -> Vec<TorsionCandidate<Filtration>> {
+        let mut candidates = Vec::new();
+        let num_degrees = self.barcodes.iter().map(|barcodes| barcodes.len()).max().unwrap_or(0);
+        for h_degree in 0..num_degrees {
+            // tag every bar with the index of its prime, then sort so that equal bars are adjacent
+            let mut tagged: Vec<(&(Filtration, Filtration), usize)> = Vec::new();
+            for (prime_index, barcodes) in self.barcodes.iter().enumerate() {
+                if let Some(barcode) = barcodes.get(h_degree) {
+                    tagged.extend(barcode.iter().map(|bar| (bar, prime_index)));
+                }
+            }
+            tagged.sort_by(|a, b| a.0.partial_cmp(b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+            let mut start = 0;
+            while start < tagged.len() {
+                let mut multiplicities = vec![0; self.primes.len()];
+                let mut end = start;
+                while end < tagged.len() && tagged[end].0 == tagged[start].0 {
+                    multiplicities[tagged[end].1] += 1;
+                    end += 1;
+                }
+                if multiplicities.iter().any(|m| *m != multiplicities[0]) {
+                    candidates.push(TorsionCandidate{ h_degree, bar: tagged[start].0.clone(), multiplicities });
+                }
+                start = end;
+            }
+        }
+        candidates
+    }
+}
+
+/// Factor a chain complex over several prime fields, and collect the barcodes
+///
+/// This runs [`factor_chain_complex`](factor_chain_complex) once for each prime.  Use
+/// [`MultiPrimeBarcodes::torsion_candidates`](MultiPrimeBarcodes::torsion_candidates) to find the bars that
+/// differ between fields.
+///
+/// # Parameters
+/// - `primes`: The orders of the coefficient fields, e.g. `&[2, 3, 5, 2147483647]`
+/// - `build_complex`: A function which, given a prime p, returns the chain complex with coefficients in Z/pZ (i.e. with `RingSpec::Modulus(p)`)
+/// - `max_homology_degree`: The max degree of which we want to compute barcodes; the complex must be able to build boundaries in degree `max_homology_degree + 1`
+/// # Returns
+/// The barcodes in degrees `0..=max_homology_degree` for each prime, or
+/// [`DecompError::NotAField`](crate::matrix::DecompError::NotAField) if one of the numbers in `primes` is not prime
+///
+/// # Examples
+///
+/// The barycentric subdivision of the 6-vertex real projective plane is a clique complex: its vertices are
+/// the faces of the triangulation, and two faces are adjacent if one contains the other.  Over Z/2Z there is an
+/// extra bar in degrees 1 and 2, which the other fields do not see.
+///
+/// ```
+/// use exhact::matrix::{RingSpec, RingMetadata, MajorDimension, DecompError};
+/// use exhact::clique::CliqueComplex;
+/// use exhact::chx::{factor_chain_complex_multiprime, TorsionCandidate};
+///
+/// let triangles = vec![ [0, 1, 3], [0, 1, 5], [0, 2, 4], [0, 2, 5], [0, 3, 4],
+///                       [1, 2, 3], [1, 2, 4], [1, 4, 5], [2, 3, 5], [3, 4, 5] ];
+/// let mut faces: Vec<Vec<usize>> = Vec::new();
+/// for triangle in triangles.iter() {
+///     for i in 0..3 {
+///         let vertex = vec![triangle[i]];
+///         let edge: Vec<usize> = triangle.iter().cloned().filter(|v| *v != triangle[i]).collect();
+///         if !faces.contains(&vertex) { faces.push(vertex); }
+///         if !faces.contains(&edge) { faces.push(edge); }
+///     }
+///     faces.push(triangle.to_vec());
+/// }
+/// let is_face_of = |a: &Vec<usize>, b: &Vec<usize>| a.iter().all(|v| b.contains(v));
+/// let dismat: Vec<Vec<i64>> = faces.iter().map(|a| {
+///     faces.iter().map(|b| {
+///         if a == b { 0 } else if is_face_of(a, b) || is_face_of(b, a) { 1 } else { 2 }
+///     }).collect()
+/// }).collect();
+///
+/// let build_complex = |prime: usize| CliqueComplex {
+///     dissimilarity_matrix: dismat.clone(),
+///     dissimilarity_value_max: 1,
+///     safe_homology_degrees_to_build_boundaries: (1..4).collect(),
+///     major_dimension: MajorDimension::Row,
+///     ringmetadata: RingMetadata{
+///         ringspec: RingSpec::Modulus(prime),
+///         identity_additive: 0i64,
+///         identity_multiplicative: 1i64,
+///     },
+///     simplex_count: Vec::new()
+/// };
+///
+/// let multiprime = factor_chain_complex_multiprime(&[2, 3, 5, 2147483647], build_complex, 2).unwrap();
+/// assert_eq!( multiprime.torsion_candidates(),
+///             vec![ TorsionCandidate{ h_degree: 1, bar: (1, 1), multiplicities: vec![1, 0, 0, 0] },
+///                   TorsionCandidate{ h_degree: 2, bar: (1, 1), multiplicities: vec![1, 0, 0, 0] } ] );
+///
+/// // 4 is not a prime
+/// assert!( matches!( factor_chain_complex_multiprime(&[2, 4], build_complex, 2), Err(DecompError::NotAField(RingSpec::Modulus(4))) ) );
+/// ```
+pub fn factor_chain_complex_multiprime<MatrixIndexKey, SnzVal, Filtration, OriginalChx, ChxBuilder>(
+    primes:                 &[usize],
+    build_complex:          ChxBuilder,
+    max_homology_degree:    usize
+) -> Result<MultiPrimeBarcodes<Filtration>, DecompError> where
+MatrixIndexKey: PartialEq + Eq + Ord + Hash + Clone + Debug,
+SnzVal: RingElement + Debug,
+Filtration: Debug + PartialOrd + Clone,
+OriginalChx: ChainComplex<MatrixIndexKey, SnzVal, Filtration>,
+ChxBuilder: Fn(usize) -> OriginalChx
+{
+    let mut barcodes = Vec::with_capacity(primes.len());
+    for prime in primes.iter() {
+        let complex = build_complex(*prime);
+        let blocks = factor_chain_complex(&complex, max_homology_degree + 1)?;
+        barcodes.push((0..max_homology_degree + 1).map(|h_degree| blocks.barcode(h_degree)).collect());
+    }
+    Ok(MultiPrimeBarcodes{ primes: primes.to_vec(), barcodes })
 }
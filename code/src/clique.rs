@@ -56,12 +56,11 @@ Example:
 
 
 
-use std::ops::Neg;
 use std::convert::TryInto;
 use std::hash::Hash;
 use std::fmt::Debug;
 
-use crate::matrix::{SmOracle, RingMetadata, MajorDimension};
+use crate::matrix::{SmOracle, RingMetadata, RingElement, Ring, MajorDimension};
 use crate::chx::{ChainComplex, ChxTransformKind};
 
 use std::cmp::Ordering;
@@ -215,7 +214,7 @@ struct CofacetIter<'a, FilVal: Clone, SnzVal: Clone> {
 /// implement standard methods of Iterator for CofacetIter struct
 impl<'a, FilVal, SnzVal> Iterator for CofacetIter<'a, FilVal, SnzVal> where
 FilVal: Clone + Debug + PartialOrd,
-SnzVal: Clone + Neg<Output = SnzVal>
+SnzVal: RingElement
 {
 	type Item = (Simplex<FilVal>, SnzVal);
 
@@ -250,7 +249,7 @@ SnzVal: Clone + Neg<Output = SnzVal>
                 }
                 vertices[self.insertion_location] = vertices[self.insertion_location+1];
                 self.insertion_location += 1;
-                self.coeff = -self.coeff.clone();
+                self.coeff = self.clique.ringmetadata.negate(&self.coeff);
             }
             if flag { self.candidate_location += 1; continue; }
 
@@ -278,7 +277,7 @@ struct FacetIter<'a, FilVal: Clone + Debug, SnzVal: Clone> {
 /// implement standard methods of Iterator for FacetIter struct
 impl<FilVal, SnzVal> Iterator for FacetIter<'_, FilVal, SnzVal> where
 FilVal: Clone + PartialOrd + Debug + Ord,
-SnzVal: Clone + Neg<Output = SnzVal>
+SnzVal: RingElement
 {
 	type Item = (Simplex<FilVal>, SnzVal);
 
@@ -288,7 +287,7 @@ SnzVal: Clone + Neg<Output = SnzVal>
         simplex.vertices.remove(self.removal_location);
         if let Some(diam) = self.clique.diam(&simplex.vertices) {
             simplex.filvalue = diam;
-            self.coeff = -self.coeff.clone();
+            self.coeff = self.clique.ringmetadata.negate(&self.coeff);
             self.removal_location += 1;
             return Some((simplex, self.coeff.clone()));
         } else {
@@ -300,14 +299,14 @@ SnzVal: Clone + Neg<Output = SnzVal>
 // See matrix.rs file for specific definition of SmOracle trait
 impl<FilVal, SnzVal> SmOracle<Simplex<FilVal>, Simplex<FilVal>, SnzVal> for CliqueBoundaryMatrix<FilVal, SnzVal> where
 FilVal: PartialOrd + Clone + Debug + Eq + Hash + Ord,
-SnzVal: Neg<Output = SnzVal> + Clone
+SnzVal: RingElement
 {
 
     fn ring( &self ) -> &RingMetadata<SnzVal> {
         &self.ringmetadata
     }
 
-	fn maj_dim( &self ) -> MajorDimension { MajorDimension::Row }
+	fn maj_dim( &self ) -> MajorDimension { self.majordimension.clone() }
 
 	fn maj_itr(&self, majkey: &Simplex<FilVal>) -> Box<dyn Iterator<Item=(Simplex<FilVal>, SnzVal)> + '_> {
         if self.majordimension == MajorDimension::Row {
@@ -330,7 +329,7 @@ SnzVal: Neg<Output = SnzVal> + Clone
                 clique: &self,
                 simp: majkey.clone(),
                 removal_location: 0,
-                coeff: -self.ringmetadata.identity_multiplicative.clone()
+                coeff: self.ringmetadata.negate(&self.ringmetadata.identity_multiplicative)
             })
         }
 	}
@@ -341,7 +340,7 @@ SnzVal: Neg<Output = SnzVal> + Clone
                 clique: &self,
                 simp: minkey.clone(),
                 removal_location: 0,
-                coeff: -self.ringmetadata.identity_multiplicative.clone()
+                coeff: self.ringmetadata.negate(&self.ringmetadata.identity_multiplicative)
             })
         } else {
             let mut vertices = minkey.vertices.clone();
@@ -448,7 +447,7 @@ SnzVal: Clone
 
 // Implimentation of ChainComplex trait. See chx.rs for definition of ChainComplex trait.
 impl<SnzVal, FilVal> ChainComplex<Simplex<FilVal>, SnzVal, FilVal> for CliqueComplex<SnzVal, FilVal> where
-SnzVal: Clone + Neg<Output = SnzVal> + Debug,
+SnzVal: RingElement + Debug,
 FilVal: PartialOrd + Copy + Debug + Eq + Hash + Ord
 {
     type Matrix = CliqueBoundaryMatrix<FilVal, SnzVal>;
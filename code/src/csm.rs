@@ -17,10 +17,13 @@ Compressed sparse matrices (similar to CSC/CSR format)
 use sprs::CsMat;
 
 use std::iter::FromIterator;
-use crate::matrix::{SmOracle, RingMetadata, MajorDimension};
-use std::collections::HashMap;
+use std::sync::OnceLock;
+use std::cmp::Ordering;
+use crate::matrix::{SmOracle, RingMetadata, MajorDimension, RingElement, Semiring};
+use std::collections::{HashMap, HashSet};
 use std::hash::Hash;
 use std::fmt::Debug;
+use crate::solver::add_assign_hash;
 
 /**
 A fairly conventional implementation of compressed sparse format (CSC and CSR) matrices, with a few differences:
@@ -31,10 +34,12 @@ A fairly conventional implementation of compressed sparse format (CSC and CSR) m
     * The **major fields** of a row-major `CSM` are its rows.  Row-major `CSM`s are almost the same as CSR's.
     * The **major fields** of a column-major `CSM` are its columns.  Column-major `CSM`s are almost the same as CSC's.
 
-* **Minor keys** The major fields of a `CSM` are always indexed by integers. But the minor fields can be indexed by anything (e.g. simplices).  The `MinKey` type parameter records the type of the minor keys.
+* **Minor keys** The major fields of a `CSM` are always indexed by integers. But the minor fields can be indexed by anything (e.g. simplices).  The `MinKey` type parameter records the type of the minor keys.  To index the major fields by something other than integers, wrap the `CSM` in a [`KeyedCsm`](KeyedCsm).
 
 * **Coefficient ring** In order to accomodate a wide range of coefficient rings, each `CSM` stores some extra data about what its coefficient ring is and how to work with it.
 
+* **Sorted mode** By default the entries of each major field are stored in whatever order they were appended (for [`append_maj`](CSM::append_maj), the iteration order of a hash map).  A `CSM` created with [`new_sorted`](CSM::new_sorted), or sorted with [`sort_fields`](CSM::sort_fields), keeps the entries of each major field in strictly increasing order of minor key.  In sorted mode `maj_itr` is guaranteed to return entries in this order, and `entry`, `maj_fn`, `min_fn` and `min_itr` look up minor keys by binary search rather than a linear scan.
+
 The attributes of a `CSM` struct are as follows:
 
 * `ringmetadata` = a struct that encodes all the information needed to work with the coefficient ring
@@ -42,6 +47,7 @@ The attributes of a `CSM` struct are as follows:
 * `majdim` = major dimension (row or column)
 * `snzval[majptr[i]..majptr[i+1]]` = the structurally nonzero coefficients of the `i`th major field
 * `snzind[majptr[i]..majptr[i+1]]` = the indices of the structurally nonzero coefficients of the `i`th major field
+* `minkey_order` (private) = `None`, or the order in which the minor keys of each major field are sorted (see sorted mode, above); use [`is_sorted`](CSM::is_sorted) to check for sorted mode
 
 
 ```
@@ -55,6 +61,7 @@ pub struct CSM<MinKey, SnzVal: Clone> {
     pub majptr: Vec<usize>,                  // maj end pointer
     pub minind: Vec<MinKey>,                 // min indices
     pub snzval: Vec<SnzVal>,                 // structural non-zero values
+    minkey_order: Option<fn(&MinKey, &MinKey) -> Ordering>,       // if not None, each major field is sorted in this order
 }
 
 // Methods for CSM struct
@@ -77,7 +84,8 @@ SnzVal: Clone + PartialEq + Debug
             majdim,
 			majptr: vec![0],
 			minind: Vec::new(),
-			snzval: Vec::new()
+			snzval: Vec::new(),
+            minkey_order: None
 		}
 	}
 
@@ -100,7 +108,8 @@ SnzVal: Clone + PartialEq + Debug
             majdim,
             majptr,
             minind: Vec::with_capacity(capacity),
-            snzval: Vec::with_capacity(capacity)
+            snzval: Vec::with_capacity(capacity),
+            minkey_order: None
         }
     }
 
@@ -120,6 +129,7 @@ SnzVal: Clone + PartialEq + Debug
 	/// Reverse the order of majs
 	pub fn reverse_maj_order(&mut self) {
 		let mut reversed = CSM::new(self.majdim.clone(), self.ringmetadata.clone());
+        reversed.minkey_order = self.minkey_order;
 		for ii in 0..self.nummaj {
 			let maj_index = self.nummaj-1-ii;
             reversed.append_maj(&mut self.maj_hash(&maj_index));
@@ -129,11 +139,24 @@ SnzVal: Clone + PartialEq + Debug
 
 	/// Add a new maj to the CSM
 	///
+	/// In sorted mode the entries are sorted by minor key before they are stored.
+	///
 	/// # Parameters
 	/// - `hash`: A hash map representing the sparse major row
 	pub fn append_maj(&mut self, hash: &mut HashMap<MinKey, SnzVal>) {
-        for (key, val) in hash.drain() {
-            self.push_snzval(key, val);
+        match self.minkey_order {
+            Some(order) => {
+                let mut entries: Vec<(MinKey, SnzVal)> = hash.drain().collect();
+                entries.sort_by(|x, y| order(&x.0, &y.0));
+                for (key, val) in entries {
+                    self.push_snzval(key, val);
+                }
+            }
+            None => {
+                for (key, val) in hash.drain() {
+                    self.push_snzval(key, val);
+                }
+            }
         }
 		self.majptr.push(self.minind.len());
         self.nummaj += 1;
@@ -141,6 +164,8 @@ SnzVal: Clone + PartialEq + Debug
 
 	/// Add a single new entry to the CSM without updating the majptr
 	///
+	/// In sorted mode, the caller is responsible for pushing the entries of each major field in increasing order of minor key.
+	///
 	/// # Parameters
 	/// - `ind`: The colum index of the entry
 	/// - `val`: The value of the entry
@@ -166,8 +191,491 @@ SnzVal: Clone + PartialEq + Debug
 
 }
 
+// Methods for CSM structs in sorted mode
+impl<MinKey, SnzVal> CSM<MinKey, SnzVal> where
+MinKey: Clone + Debug + Eq + PartialEq + Hash + Ord,
+SnzVal: Clone + PartialEq + Debug
+{
+    /// Create a trivial CSM in sorted mode, which keeps the entries of each major field in increasing order of minor key
+    ///
+    /// ```
+    /// use exhact::matrix::{RingSpec, RingMetadata, MajorDimension, SmOracle};
+    /// use exhact::csm::CSM;
+    ///
+    /// let ringmetadata = RingMetadata{ ringspec: RingSpec::Modulus(7), identity_additive: 0i64, identity_multiplicative: 1i64 };
+    /// let mut matrix = CSM::new_sorted(MajorDimension::Row, ringmetadata);
+    /// matrix.append_maj(&mut vec![(5, 1), (0, 2), (3, 3)].into_iter().collect());
+    ///
+    /// assert_eq!(matrix.maj_itr(&0).collect::<Vec<_>>(), vec![(0, 2), (3, 3), (5, 1)]);
+    /// assert_eq!(matrix.entry(&0, &3), Some(3));
+    /// assert_eq!(matrix.entry(&0, &4), None);
+    /// ```
+    ///
+    /// # Parameters
+    /// - `majdim`: major dimension
+    /// - `ringmetadata`: Ring meta data of structural non zero values in CSM
+    pub fn new_sorted(
+        majdim:             MajorDimension,
+        ringmetadata:       RingMetadata<SnzVal>
+    ) -> CSM<MinKey, SnzVal> {
+        let mut output = CSM::new(majdim, ringmetadata);
+        output.minkey_order = Some(MinKey::cmp);
+        output
+    }
+
+    /// Create a trivial CSM in sorted mode with the specified capacity
+    ///
+    /// The caller must append the entries of each major field in increasing order of minor key, e.g. with [`push_snzval`](CSM::push_snzval).
+    ///
+    /// # Parameters
+    /// - `capacity`: The capacity of allocation
+    /// - `majdim`: major dimension
+    /// - `ringmetadata`: Ring meta data of structural non zero values in CSM
+    pub fn with_capacity_sorted(
+        capacity:           usize,
+        majdim:             MajorDimension,
+        ringmetadata:       RingMetadata<SnzVal>
+    ) -> CSM<MinKey, SnzVal> {
+        let mut output = CSM::with_capacity(capacity, majdim, ringmetadata);
+        output.minkey_order = Some(MinKey::cmp);
+        output
+    }
+
+    /// Sort the entries of each major field by minor key, and switch to sorted mode
+    ///
+    /// Each major field must contain each minor key at most once.
+    pub fn sort_fields(&mut self) {
+        for majind in 0..self.nummaj {
+            let range = self.majptr[majind]..self.majptr[majind+1];
+            let mut entries: Vec<(MinKey, SnzVal)> = self.minind[range.clone()].iter().cloned()
+                .zip(self.snzval[range.clone()].iter().cloned())
+                .collect();
+            entries.sort_by(|x, y| x.0.cmp(&y.0));
+            for (pointer, (key, val)) in range.zip(entries) {
+                self.minind[pointer] = key;
+                self.snzval[pointer] = val;
+            }
+        }
+        self.minkey_order = Some(MinKey::cmp);
+    }
+}
+
+/// A violation of one of the invariants of a [`CSM`](CSM), as reported by [`CSM::validate`](CSM::validate)
+#[derive(Clone, Debug, PartialEq)]
+pub enum CsmError<MinKey, SnzVal> {
+    /// `majptr` should have length `nummaj + 1`
+    MajptrLength{ nummaj: usize, majptr_len: usize },
+    /// `majptr` should start at 0, never decrease, and end at the number of structural nonzeros
+    MajptrNotMonotone{ position: usize },
+    /// `minind` and `snzval` should have the same length
+    LengthMismatch{ minind_len: usize, snzval_len: usize },
+    /// A structural nonzero that is zero according to the ring
+    ExplicitZero{ majind: usize, minkey: MinKey },
+    /// A minor key that appears more than once in the same major field
+    DuplicateMinKey{ majind: usize, minkey: MinKey },
+    /// A coefficient that changes under `RingMetadata::simplify` (e.g. a value outside `[0, p)` for `RingSpec::Modulus(p)`)
+    UnreducedCoefficient{ majind: usize, minkey: MinKey, value: SnzVal },
+    /// In sorted mode, a major field whose minor keys are not in strictly increasing order
+    UnsortedField{ majind: usize },
+    /// An entry on the wrong side of the diagonal, for [`CSM::validate_unitriangular`](CSM::validate_unitriangular)
+    OffTriangle{ majind: usize, minkey: MinKey },
+    /// A diagonal entry that is missing or not equal to one, for [`CSM::validate_unitriangular`](CSM::validate_unitriangular)
+    DiagonalNotOne{ majind: usize },
+}
+
+// Validation of CSM structs
+impl<MinKey, SnzVal> CSM<MinKey, SnzVal> where
+MinKey: Clone + Debug + Eq + Hash,
+SnzVal: RingElement + Debug
+{
+    /// Check the invariants of the CSM, and return every violation found
+    ///
+    /// If the pointers (`majptr`) are inconsistent, then only the pointer errors are reported, since the major fields cannot be read.
+    /// Hand-built CSMs should be checked before they are passed to functions like [`decomp_row`](crate::decomp_row::decomp_row),
+    /// which otherwise fail with index errors far from the source of the problem.
+    ///
+    /// ```
+    /// use exhact::matrix::{RingSpec, RingMetadata, MajorDimension};
+    /// use exhact::csm::{CSM, CsmError};
+    ///
+    /// let ringmetadata = RingMetadata{ ringspec: RingSpec::Modulus(3), identity_additive: 0i64, identity_multiplicative: 1i64 };
+    /// let mut matrix = CSM::new(MajorDimension::Row, ringmetadata);
+    /// matrix.push_snzval(0usize, 1);
+    /// matrix.push_snzval(0, 4);
+    /// matrix.majptr.push(2);
+    /// matrix.nummaj += 1;
+    ///
+    /// assert_eq!(matrix.validate(), Err(vec![
+    ///     CsmError::DuplicateMinKey{ majind: 0, minkey: 0 },
+    ///     CsmError::UnreducedCoefficient{ majind: 0, minkey: 0, value: 4 },
+    /// ]));
+    /// ```
+    pub fn validate(&self) -> Result<(), Vec<CsmError<MinKey, SnzVal>>> {
+        let mut errors = Vec::new();
+
+        if self.minind.len() != self.snzval.len() {
+            errors.push(CsmError::LengthMismatch{ minind_len: self.minind.len(), snzval_len: self.snzval.len() });
+        }
+        if self.majptr.len() != self.nummaj + 1 {
+            errors.push(CsmError::MajptrLength{ nummaj: self.nummaj, majptr_len: self.majptr.len() });
+        }
+        let numsnz = self.minind.len().min(self.snzval.len());
+        for (position, pointer) in self.majptr.iter().enumerate() {
+            let previous = if position == 0 { 0 } else { self.majptr[position-1] };
+            let last = position + 1 == self.majptr.len();
+            if *pointer < previous || *pointer > numsnz || (last && *pointer != numsnz) || (position == 0 && *pointer != 0) {
+                errors.push(CsmError::MajptrNotMonotone{ position });
+            }
+        }
+        if !errors.is_empty() { return Err(errors); }
+
+        let ring = &self.ringmetadata;
+        let mut seen = HashSet::new();
+        for majind in 0..self.nummaj {
+            seen.clear();
+            let range = self.majptr[majind]..self.majptr[majind+1];
+            for ii in range.clone() {
+                let minkey = &self.minind[ii];
+                let value = &self.snzval[ii];
+                if !seen.insert(minkey) {
+                    errors.push(CsmError::DuplicateMinKey{ majind, minkey: minkey.clone() });
+                }
+                if ring.is_0(value) {
+                    errors.push(CsmError::ExplicitZero{ majind, minkey: minkey.clone() });
+                } else if ring.simplify(value) != *value {
+                    errors.push(CsmError::UnreducedCoefficient{ majind, minkey: minkey.clone(), value: value.clone() });
+                }
+            }
+            if let Some(order) = self.minkey_order {
+                if self.minind[range].windows(2).any(|pair| order(&pair[0], &pair[1]) != Ordering::Less) {
+                    errors.push(CsmError::UnsortedField{ majind });
+                }
+            }
+        }
+
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+}
+
+// Validation of CSM structs with integer minor keys
+impl<SnzVal> CSM<usize, SnzVal> where
+SnzVal: RingElement + Debug
+{
+    /// Check the invariants of the CSM (see [`validate`](CSM::validate)), and also that it is unitriangular
+    ///
+    /// Rows and columns are both indexed by integers, and the matrix is unitriangular if each diagonal entry is one, and
+    /// every other entry lies strictly above the diagonal (if `upper` is true) or strictly below it (if `upper` is false).
+    /// For example, the row operation matrices computed by [`decomp_row`](crate::decomp_row::decomp_row) are lower unitriangular.
+    ///
+    /// # Parameters
+    /// - `upper`: check for an upper unitriangular matrix if true, and a lower unitriangular matrix if false
+    pub fn validate_unitriangular(&self, upper: bool) -> Result<(), Vec<CsmError<usize, SnzVal>>> {
+        self.validate()?;
+
+        // an entry in major field i and minor field j is above the diagonal if its row index is smaller than its column index
+        let above_diagonal = |majind: usize, minind: usize| match self.majdim {
+            MajorDimension::Row => majind < minind,
+            MajorDimension::Col => minind < majind
+        };
+
+        let ring = &self.ringmetadata;
+        let mut errors = Vec::new();
+        for majind in 0..self.nummaj {
+            let mut diagonal = None;
+            for ii in self.majptr[majind]..self.majptr[majind+1] {
+                let minind = self.minind[ii];
+                if minind == majind {
+                    diagonal = Some(&self.snzval[ii]);
+                } else if above_diagonal(majind, minind) != upper {
+                    errors.push(CsmError::OffTriangle{ majind, minkey: minind });
+                }
+            }
+            if !diagonal.is_some_and(|val| *val == ring.identity_multiplicative) {
+                errors.push(CsmError::DiagonalNotOne{ majind });
+            }
+        }
+
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+}
+
+// Lookup of a single entry in a major field
+impl<MinKey: PartialEq, SnzVal: Clone> CSM<MinKey, SnzVal> {
+    /// True if the CSM is in sorted mode, that is, if the entries of each major field are in strictly increasing order of minor key
+    pub fn is_sorted(&self) -> bool {
+        self.minkey_order.is_some()
+    }
+
+    /// The position in `minind` and `snzval` of the entry with minor key `minkey` in major field `majind`, if there is one
+    ///
+    /// Uses binary search in sorted mode, and a linear scan otherwise.
+    pub fn find_in_maj(&self, majind: usize, minkey: &MinKey) -> Option<usize> {
+        let start = self.majptr[majind];
+        let end = self.majptr[majind+1];
+        match self.minkey_order {
+            Some(order) => self.minind[start..end].binary_search_by(|key| order(key, minkey)).ok().map(|ii| start + ii),
+            None => (start..end).find(|ii| self.minind[*ii] == *minkey)
+        }
+    }
+}
+
+/// Conversions between CSM and [`sprs::CsMat`](sprs::CsMat)
+impl<SnzVal> CSM<usize, SnzVal> where
+SnzVal: RingElement + Debug
+{
+    /// Convert to a `sprs::CsMat`: a CSR matrix if the CSM is row-major, and a CSC matrix if it is column-major
+    ///
+    /// Indices within each major field are sorted (as `sprs` requires), repeated indices are summed, and zero coefficients are dropped.
+    ///
+    /// # Parameters
+    /// - `nummin`: Number of minor indices (e.g. the number of columns of a row-major CSM); every minor index must be smaller than this
+    ///
+    /// ```
+    /// use exhact::matrix::{RingSpec, RingMetadata, MajorDimension, SmOracle};
+    /// use exhact::csm::CSM;
+    ///
+    /// let ringmetadata = RingMetadata{ ringspec: RingSpec::Modulus(5), identity_additive: 0, identity_multiplicative: 1 };
+    /// let mut matrix: CSM<usize, i64> = CSM::new(MajorDimension::Row, ringmetadata.clone());
+    /// matrix.append_maj(&mut vec![(2, 3), (0, 1)].into_iter().collect());
+    /// matrix.append_maj(&mut vec![(1, 7)].into_iter().collect());
+    ///
+    /// let csmat = matrix.to_csmat(3);
+    /// assert!(csmat.is_csr());
+    /// assert_eq!(csmat.get(0, 2), Some(&3));
+    ///
+    /// // the product with the transpose is [[10, 0], [0, 49]]; coefficients are reduced modulo 5 on the way back
+    /// let product = &csmat * &csmat.transpose_view();
+    /// let matrix = CSM::from_csmat(&product.to_csr(), ringmetadata);
+    /// assert_eq!(matrix.entry(&0, &0), None);
+    /// assert_eq!(matrix.entry(&1, &1), Some(4));
+    /// ```
+    pub fn to_csmat(&self, nummin: usize) -> CsMat<SnzVal> {
+        let ring = &self.ringmetadata;
+        let mut indptr = Vec::with_capacity(self.nummaj + 1);
+        let mut indices = Vec::with_capacity(self.minind.len());
+        let mut data = Vec::with_capacity(self.snzval.len());
+        indptr.push(0);
+        for majind in 0..self.nummaj {
+            let mut field: Vec<(usize, SnzVal)> = (self.majptr[majind]..self.majptr[majind+1])
+                .map(|ii| (self.minind[ii], self.snzval[ii].clone()))
+                .collect();
+            field.sort_by_key(|(minind, _)| *minind);
+            let mut start = 0;
+            while start < field.len() {
+                let minind = field[start].0;
+                let mut value = field[start].1.clone();
+                let mut end = start + 1;
+                while end < field.len() && field[end].0 == minind {
+                    value = ring.add(&value, &field[end].1);
+                    end += 1;
+                }
+                if !ring.is_0(&value) {
+                    assert!(minind < nummin, "minor index {} is out of bounds for nummin = {}", minind, nummin);
+                    indices.push(minind);
+                    data.push(ring.simplify(&value));
+                }
+                start = end;
+            }
+            indptr.push(indices.len());
+        }
+        match self.majdim {
+            MajorDimension::Row => CsMat::new((self.nummaj, nummin), indptr, indices, data),
+            MajorDimension::Col => CsMat::new_csc((nummin, self.nummaj), indptr, indices, data),
+        }
+    }
+
+    /// Build a CSM from a `sprs::CsMat`: a CSR matrix becomes a row-major CSM, and a CSC matrix becomes a column-major CSM
+    ///
+    /// Since `sprs` keeps indices sorted, the result is in sorted mode.  Coefficients are simplified with `ringmetadata` (e.g. reduced modulo p), and those that become zero are dropped.
+    ///
+    /// # Parameters
+    /// - `csmat`: The matrix to convert
+    /// - `ringmetadata`: Ring meta data of the coefficients
+    pub fn from_csmat(csmat: &CsMat<SnzVal>, ringmetadata: RingMetadata<SnzVal>) -> CSM<usize, SnzVal> {
+        let majdim = if csmat.is_csr() { MajorDimension::Row } else { MajorDimension::Col };
+        let mut output = CSM::with_capacity_sorted(csmat.nnz(), majdim, ringmetadata);
+        for field in csmat.outer_iterator() {
+            for (minind, val) in field.iter() {
+                let value = output.ringmetadata.simplify(val);
+                if !output.ringmetadata.is_0(&value) {
+                    output.push_snzval(minind, value);
+                }
+            }
+            output.majptr.push(output.minind.len());
+            output.nummaj += 1;
+        }
+        output
+    }
+}
+
+/// Arithmetic with CSMs
+///
+/// Products and sums are computed one major field at a time, reducing coefficients with the `RingMetadata` of `self`; the results are in sorted mode.
+///
+/// ```
+/// use exhact::matrix::{RingSpec, RingMetadata, MajorDimension, SmOracle};
+/// use exhact::csm::CSM;
+///
+/// let ringmetadata = RingMetadata{ ringspec: RingSpec::Modulus(5), identity_additive: 0i64, identity_multiplicative: 1i64 };
+///
+/// // 1 2
+/// // 0 1
+/// let mut matrix = CSM::new(MajorDimension::Row, ringmetadata.clone());
+/// matrix.append_maj(&mut vec![(0, 1), (1, 2)].into_iter().collect());
+/// matrix.append_maj(&mut vec![(1, 1)].into_iter().collect());
+///
+/// // the square is [[1, 4], [0, 1]], and the cube is [[1, 6], [0, 1]] = [[1, 1], [0, 1]] modulo 5
+/// let cube = matrix.multiply(&matrix).multiply(&matrix);
+/// assert_eq!(cube.entry(&0, &1), Some(1));
+///
+/// // swapping the rows is the same as multiplying on the left by a permutation matrix
+/// let swap = CSM::permutation(&[1, 0], MajorDimension::Row, ringmetadata.clone());
+/// let swapped = swap.multiply(&matrix);
+/// assert_eq!(swapped.maj_hash(&0), matrix.maj_hash(&1));
+///
+/// // the same product, as a column-major matrix
+/// let swapped = swapped.with_major_dim(MajorDimension::Col, 2);
+/// assert_eq!(swapped.maj_itr(&1).collect::<Vec<_>>(), vec![(0, 1), (1, 2)]);
+///
+/// // the sum with the identity is [[2, 2], [0, 2]]
+/// let sum = matrix.add(&CSM::identity(2, MajorDimension::Row, ringmetadata));
+/// assert_eq!(sum.maj_itr(&0).collect::<Vec<_>>(), vec![(0, 2), (1, 2)]);
+/// ```
+impl<SnzVal> CSM<usize, SnzVal> where
+SnzVal: RingElement + Debug
+{
+    /// The `size x size` identity matrix
+    ///
+    /// # Parameters
+    /// - `size`: number of rows (and columns)
+    /// - `majdim`: major dimension
+    /// - `ringmetadata`: Ring meta data of structural non zero values in CSM
+    pub fn identity(
+        size:               usize,
+        majdim:             MajorDimension,
+        ringmetadata:       RingMetadata<SnzVal>
+    ) -> CSM<usize, SnzVal> {
+        let permutation: Vec<usize> = (0..size).collect();
+        CSM::permutation(&permutation, majdim, ringmetadata)
+    }
+
+    /// The permutation matrix whose `i`th major field has a single nonzero entry, equal to one, in minor position `permutation[i]`
+    ///
+    /// For a row-major matrix `P`, this means `P[i, permutation[i]] = 1`, so `P * A` is the matrix whose `i`th row is row `permutation[i]` of `A`.
+    ///
+    /// # Parameters
+    /// - `permutation`: a permutation of `0..permutation.len()`
+    /// - `majdim`: major dimension
+    /// - `ringmetadata`: Ring meta data of structural non zero values in CSM
+    pub fn permutation(
+        permutation:        &[usize],
+        majdim:             MajorDimension,
+        ringmetadata:       RingMetadata<SnzVal>
+    ) -> CSM<usize, SnzVal> {
+        let mut seen = vec![false; permutation.len()];
+        for minind in permutation.iter() {
+            assert!(*minind < permutation.len() && !seen[*minind], "{:?} is not a permutation", permutation);
+            seen[*minind] = true;
+        }
+
+        let one = ringmetadata.identity_multiplicative.clone();
+        let mut output = CSM::with_capacity_sorted(permutation.len(), majdim, ringmetadata);
+        for minind in permutation.iter() {
+            output.push_snzval(*minind, one.clone());
+            output.majptr.push(output.minind.len());
+            output.nummaj += 1;
+        }
+        output
+    }
+
+    /// The product `self * other`, with the same major dimension as the two factors
+    ///
+    /// Both factors must have the same major dimension; use [`with_major_dim`](CSM::with_major_dim) to change the major dimension of one of them first.
+    /// The product has as many major fields as `self` (if row-major) or `other` (if column-major).
+    pub fn multiply(&self, other: &CSM<usize, SnzVal>) -> CSM<usize, SnzVal> {
+        assert!(self.majdim == other.majdim, "both factors of a CSM product must have the same major dimension");
+
+        // for row-major factors, row i of the product is a combination of rows of `other`, with coefficients from row i of `self`;
+        // for column-major factors, column j of the product is a combination of columns of `self`, with coefficients from column j of `other`
+        let (coefficients, fields) = match self.majdim {
+            MajorDimension::Row => (self, other),
+            MajorDimension::Col => (other, self)
+        };
+
+        let ring = &self.ringmetadata;
+        let mut output = CSM::new_sorted(self.majdim.clone(), ring.clone());
+        let mut product = HashMap::new();
+        for majind in 0..coefficients.nummaj {
+            product.clear();
+            for (key, scale) in coefficients.maj_itr(&majind) {
+                if key >= fields.nummaj { continue; }   // a zero major field
+                add_assign_hash(ring, &mut product, &mut fields.maj_hash(&key), &scale);
+            }
+            output.append_maj(&mut reduce_hash(ring, &mut product));
+        }
+        output
+    }
+
+    /// The sum `self + other`
+    ///
+    /// Both summands must have the same major dimension.  The sum has as many major fields as the larger of the two.
+    pub fn add(&self, other: &CSM<usize, SnzVal>) -> CSM<usize, SnzVal> {
+        assert!(self.majdim == other.majdim, "both summands of a CSM sum must have the same major dimension");
+
+        let ring = &self.ringmetadata;
+        let one = ring.identity_multiplicative.clone();
+        let mut output = CSM::new_sorted(self.majdim.clone(), ring.clone());
+        let mut sum = HashMap::new();
+        for majind in 0..self.nummaj.max(other.nummaj) {
+            sum.clear();
+            for summand in [self, other] {
+                if majind < summand.nummaj {
+                    add_assign_hash(ring, &mut sum, &mut summand.maj_hash(&majind), &one);
+                }
+            }
+            output.append_maj(&mut reduce_hash(ring, &mut sum));
+        }
+        output
+    }
+
+    /// The same matrix, stored with major dimension `majdim`
+    ///
+    /// If `majdim` is the current major dimension this is a copy; otherwise the result has `nummin` major fields.
+    ///
+    /// # Parameters
+    /// - `majdim`: the major dimension of the result
+    /// - `nummin`: Number of minor indices of `self` (e.g. the number of columns of a row-major CSM); every minor index must be smaller than this
+    pub fn with_major_dim(&self, majdim: MajorDimension, nummin: usize) -> CSM<usize, SnzVal> {
+        if majdim == self.majdim {
+            let mut output = CSM::with_capacity(self.snzval.len(), majdim, self.ringmetadata.clone());
+            output.nummaj = self.nummaj;
+            output.majptr = self.majptr.clone();
+            output.minind = self.minind.clone();
+            output.snzval = self.snzval.clone();
+            output.minkey_order = self.minkey_order;
+            return output;
+        }
+        let mut output = transpose(nummin, self);
+        output.majdim = majdim;
+        output
+    }
+}
+
+/// Simplify the coefficients of a sparse vector, dropping those that become zero
+fn reduce_hash<SnzVal: RingElement>(
+    ringmetadata:   &RingMetadata<SnzVal>,
+    hash:           &mut HashMap<usize, SnzVal>
+) -> HashMap<usize, SnzVal> {
+    hash.drain()
+        .map(|(key, val)| (key, ringmetadata.simplify(&val)))
+        .filter(|(_, val)| !ringmetadata.is_0(val))
+        .collect()
+}
+
 /// Get the transpose of a CSM matrix with the same major dimension as the input square matrix
 ///
+/// The result is in sorted mode.
+///
 /// # Parameters
 /// - `matrix`: A square CSM matrix whoes minor index are integers
 /// - `nummin`: Number of columns of the input sparse matrix
@@ -177,7 +685,10 @@ pub fn transpose<SnzVal: Clone + PartialEq + Debug>(nummin: usize, matrix: &CSM<
     let mut majptr = vec![0; nummaj+2];
     let num_val = matrix.minind.len();
     let mut minind = vec![0; num_val];
-    let mut snzval = vec![matrix.snzval[0].clone(); num_val];
+    let mut snzval = match matrix.snzval.first() {
+        Some(val) => vec![val.clone(); num_val],
+        None => Vec::new()     // an empty matrix
+    };
 
     // count per column
     for ii in 0..num_val {
@@ -209,7 +720,8 @@ pub fn transpose<SnzVal: Clone + PartialEq + Debug>(nummin: usize, matrix: &CSM<
         majdim: matrix.majdim.clone(),
         majptr,
         minind,
-        snzval
+        snzval,
+        minkey_order: Some(usize::cmp)
     };
 }
 
@@ -228,11 +740,9 @@ impl<MinInd: PartialEq, SnzVal: Clone> Iterator for MajItr<'_, MinInd, SnzVal>{
     fn next( &mut self ) -> Option<Self::Item> {
         // look for the next row with a structurally nonzero value in this column
         for thisrow in self.next_maj_ind..self.csm.nummaj {
-            for pointer in self.csm.majptr[thisrow]..self.csm.majptr[thisrow+1]{
-                if self.csm.minind[pointer] == self.minind {
-                    self.next_maj_ind = thisrow + 1;
-                    return Some((thisrow, self.csm.snzval[pointer].clone()));
-                }
+            if let Some(pointer) = self.csm.find_in_maj(thisrow, &self.minind) {
+                self.next_maj_ind = thisrow + 1;
+                return Some((thisrow, self.csm.snzval[pointer].clone()));
             }
         }
         // if there is no such row, then return None and reset the iterator
@@ -252,7 +762,7 @@ SnzVal: Clone
         &self.ringmetadata
     }
 
-	fn maj_dim( &self ) -> MajorDimension { MajorDimension::Row }
+	fn maj_dim( &self ) -> MajorDimension { self.majdim.clone() }
 
     fn maj_indmin( &self, majkey: &usize ) -> Option<Vec<MinKey>> {
         if self.majptr[*majkey]<self.majptr[*majkey+1] {
@@ -310,34 +820,26 @@ SnzVal: Clone
 
     fn maj_fn( &self, majkey: &usize ) -> Box<dyn Fn(MinKey) -> Option<SnzVal> + '_> {
         let maj = *majkey;
-		Box::new( move |x| {
-				for ii in self.majptr[maj]..self.majptr[maj+1]{
-					if self.minind[ii] == x {
-						return Some(self.snzval[ii].clone());
-					}
-				}
-				return None;
-			}
-		)
+		Box::new( move |x| self.find_in_maj(maj, &x).map(|ii| self.snzval[ii].clone()) )
 	}
 
 	fn min_fn( &self, minkey: &MinKey ) -> Box<dyn Fn(usize) -> Option<SnzVal> + '_> {
         let min = minkey.clone();
-		Box::new( move |x| {
-				for ii in self.majptr[x]..self.majptr[x+1] {
-					if self.minind[ii] == min {
-						return Some(self.snzval[ii].clone());
-					}
-				}
-				return None;
-			}
-		)
+		Box::new( move |x| self.find_in_maj(x, &min).map(|ii| self.snzval[ii].clone()) )
 	}
 
+    fn entry( &self, majkey: &usize, minkey: &MinKey ) -> Option<SnzVal> {
+        self.find_in_maj(*majkey, minkey).map(|ii| self.snzval[ii].clone())
+    }
+
 	fn countsnz( &self ) -> Option<usize> {
 		Some(self.snzval.len())
 	}
 
+    fn maj_itr_sorted( &self ) -> bool { self.is_sorted() }
+
+    fn min_itr_sorted( &self ) -> bool { true }
+
 	fn finiteminors( &self ) -> Option<bool> {
 		Some(true)
 	}
@@ -345,3 +847,327 @@ SnzVal: Clone
 		Some(true)
 	}
 }
+
+
+/**
+A [`CSM`](CSM) whose major fields are indexed by arbitrary keys (e.g. simplices), rather than by integers.
+
+The underlying `CSM` stores the major fields in the order they were appended; `index_2_majkey[i]` is the key of the `i`th major field, and `majkey_2_index` is the inverse map.  Major keys that were never appended are treated as indexing empty fields.
+
+A common use is to materialize part of a lazy oracle, e.g. the boundary of a [`CliqueComplex`](crate::clique::CliqueComplex), so that it can be queried repeatedly without recomputing each field:
+
+```
+use exhact::matrix::{RingSpec, RingMetadata, MajorDimension, SmOracle};
+use exhact::chx::{ChainComplex, ChxTransformKind};
+use exhact::clique::CliqueComplex;
+use exhact::csm::KeyedCsm;
+
+// a triangle in which every edge appears at filtration 1
+let chx = CliqueComplex {
+    dissimilarity_matrix: vec![vec![0, 1, 1], vec![1, 0, 1], vec![1, 1, 0]],
+    dissimilarity_value_max: 1,
+    safe_homology_degrees_to_build_boundaries: vec![1],
+    major_dimension: MajorDimension::Col,
+    ringmetadata: RingMetadata{ ringspec: RingSpec::Modulus(3), identity_additive: 0i64, identity_multiplicative: 1i64 },
+    simplex_count: Vec::new()
+};
+let oracle = chx.get_smoracle(MajorDimension::Col, ChxTransformKind::Boundary);
+let edges = chx.keys_ordered(1);
+let cached = KeyedCsm::from_oracle(&oracle, &edges);
+
+// columns of the cached boundary are indexed by simplices
+for edge in edges.iter() {
+    assert_eq!(cached.maj_hash(edge), oracle.maj_hash(edge));
+}
+let vertex = chx.keys_ordered(0)[0].clone();
+assert_eq!(cached.min_length(&vertex), 2);
+```
+*/
+pub struct KeyedCsm<MajKey, MinKey, SnzVal: Clone> {
+    pub csm: CSM<MinKey, SnzVal>,                   // the major fields, indexed by integers
+    pub index_2_majkey: Vec<MajKey>,                // the key of each major field
+    pub majkey_2_index: HashMap<MajKey, usize>,     // the index of each major key
+}
+
+// Methods for KeyedCsm struct
+impl<MajKey, MinKey, SnzVal> KeyedCsm<MajKey, MinKey, SnzVal> where
+MajKey: Clone + Debug + Eq + Hash,
+MinKey: Clone + Debug + Eq + PartialEq + Hash,
+SnzVal: Clone + PartialEq + Debug
+{
+    /// Create a trivial KeyedCsm
+    ///
+    /// # Parameters
+    /// - `majdim`: major dimension
+    /// - `ringmetadata`: Ring meta data of structural non zero values in CSM
+    pub fn new(
+        majdim:             MajorDimension,
+        ringmetadata:       RingMetadata<SnzVal>
+    ) -> KeyedCsm<MajKey, MinKey, SnzVal> {
+        KeyedCsm {
+            csm: CSM::new(majdim, ringmetadata),
+            index_2_majkey: Vec::new(),
+            majkey_2_index: HashMap::new()
+        }
+    }
+
+    /// Copy the major fields indexed by `majkeys` out of a sparse matrix oracle
+    ///
+    /// # Parameters
+    /// - `oracle`: The sparse matrix oracle to copy from
+    /// - `majkeys`: The major keys whose fields we copy
+    pub fn from_oracle<Matrix: SmOracle<MajKey, MinKey, SnzVal>>(
+        oracle:     &Matrix,
+        majkeys:    &[MajKey]
+    ) -> KeyedCsm<MajKey, MinKey, SnzVal> {
+        let mut output = KeyedCsm::new(oracle.maj_dim(), oracle.ring().clone());
+        for majkey in majkeys.iter() {
+            output.append_maj(majkey.clone(), &mut oracle.maj_hash(majkey));
+        }
+        output
+    }
+
+    /// Add a new major field, indexed by `majkey`
+    ///
+    /// # Parameters
+    /// - `majkey`: The key of the new major field; this must not already index a major field
+    /// - `hash`: A hash map representing the sparse major field
+    pub fn append_maj(&mut self, majkey: MajKey, hash: &mut HashMap<MinKey, SnzVal>) {
+        assert!(!self.majkey_2_index.contains_key(&majkey), "major key {:?} has already been appended", majkey);
+        self.majkey_2_index.insert(majkey.clone(), self.csm.nummaj);
+        self.index_2_majkey.push(majkey);
+        self.csm.append_maj(hash);
+    }
+
+    /// The position of the major field indexed by `majkey` in the underlying CSM, if there is one
+    pub fn majkey_index(&self, majkey: &MajKey) -> Option<usize> {
+        self.majkey_2_index.get(majkey).cloned()
+    }
+
+    /// Shrink the capacity of the KeyedCsm to save memory
+    pub fn shrink_to_fit(&mut self) {
+        self.csm.shrink_to_fit();
+        self.index_2_majkey.shrink_to_fit();
+        self.majkey_2_index.shrink_to_fit();
+    }
+}
+
+// See matrix.rs file for specific definition of SmOracle trait
+impl<MajKey, MinKey, SnzVal> SmOracle<MajKey, MinKey, SnzVal> for KeyedCsm<MajKey, MinKey, SnzVal> where
+MajKey: Clone + PartialEq + Eq + Hash,
+MinKey: Clone + PartialEq + Eq + Hash,
+SnzVal: Clone
+{
+    fn ring( &self ) -> &RingMetadata<SnzVal> {
+        &self.csm.ringmetadata
+    }
+
+    fn maj_dim( &self ) -> MajorDimension { self.csm.majdim.clone() }
+
+    fn maj_itr( &self, majkey: &MajKey ) -> Box<dyn Iterator<Item=(MinKey, SnzVal)> + '_> {
+        match self.majkey_2_index.get(majkey) {
+            Some(index) => self.csm.maj_itr(index),
+            None => Box::new(std::iter::empty())
+        }
+    }
+
+    fn min_itr( &self, minkey: &MinKey ) -> Box<dyn Iterator<Item=(MajKey, SnzVal)> + '_> {
+        Box::new( self.csm.min_itr(minkey).map( move |(index, val)| (self.index_2_majkey[index].clone(), val) ) )
+    }
+
+    fn entry( &self, majkey: &MajKey, minkey: &MinKey ) -> Option<SnzVal> {
+        match self.majkey_2_index.get(majkey) {
+            Some(index) => self.csm.entry(index, minkey),
+            None => None
+        }
+    }
+
+    fn maj_length( &self, majkey: &MajKey ) -> usize {
+        match self.majkey_2_index.get(majkey) {
+            Some(index) => self.csm.majptr[*index+1] - self.csm.majptr[*index],
+            None => 0
+        }
+    }
+
+    fn countsnz( &self ) -> Option<usize> {
+        self.csm.countsnz()
+    }
+
+    fn maj_itr_sorted( &self ) -> bool {
+        self.csm.maj_itr_sorted()
+    }
+
+    fn finiteminors( &self ) -> Option<bool> {
+        Some(true)
+    }
+
+    fn finitemajors( &self ) -> Option<bool> {
+        Some(true)
+    }
+}
+
+
+/**
+A [`CSM`](CSM) together with a transposed index, so that both major and minor fields can be read quickly.
+
+The minor fields of a plain `CSM` are found by scanning every major field, so each one costs time proportional to the number of structural nonzeros in the whole matrix.  A `DualCsm` builds a second copy of the matrix, in the other orientation, the first time a minor field is requested; after that each minor field costs time proportional to its length.  Major fields are read directly from the underlying `CSM`, as usual.
+
+Within each minor field, entries are listed in increasing order of major index (the same order as for a plain `CSM`).
+
+```
+use exhact::matrix::{RingSpec, RingMetadata, MajorDimension, SmOracle, Transposed};
+use exhact::csm::{CSM, DualCsm};
+
+let ringmetadata = RingMetadata{ ringspec: RingSpec::Modulus(7), identity_additive: 0i64, identity_multiplicative: 1i64 };
+let mut matrix = CSM::new(MajorDimension::Row, ringmetadata);
+matrix.append_maj(&mut vec![(0, 1), (1, 2)].into_iter().collect());
+matrix.append_maj(&mut vec![(1, 3)].into_iter().collect());
+let matrix = DualCsm::new(matrix);
+
+// column 1 of the matrix
+assert_eq!(matrix.min_itr(&1).collect::<Vec<_>>(), vec![(0, 2), (1, 3)]);
+
+// wrap in a `Transposed` struct to get a column-major view whose major fields are the columns
+let columns = Transposed::new(&matrix);
+assert!(columns.maj_dim() == MajorDimension::Col);
+assert_eq!(columns.maj_length(&0), 1);
+```
+*/
+pub struct DualCsm<MinKey, SnzVal: Clone> {
+    csm: CSM<MinKey, SnzVal>,                                               // the matrix, in its original orientation
+    transpose: OnceLock<(HashMap<MinKey, usize>, CSM<usize, SnzVal>)>,      // minor key -> position, and the transposed matrix (built on demand)
+}
+
+// Methods for DualCsm struct
+impl<MinKey, SnzVal> DualCsm<MinKey, SnzVal> where
+MinKey: Clone + Eq + Hash,
+SnzVal: Clone
+{
+    /// Wrap a CSM; the transposed index is built the first time a minor field is requested
+    ///
+    /// # Parameters
+    /// - `csm`: the underlying CSM
+    pub fn new(csm: CSM<MinKey, SnzVal>) -> DualCsm<MinKey, SnzVal> {
+        DualCsm { csm, transpose: OnceLock::new() }
+    }
+
+    /// The underlying CSM
+    pub fn as_csm(&self) -> &CSM<MinKey, SnzVal> {
+        &self.csm
+    }
+
+    /// Unwrap the underlying CSM, discarding the transposed index
+    pub fn into_inner(self) -> CSM<MinKey, SnzVal> {
+        self.csm
+    }
+
+    /// The transposed index: a map from minor keys to major positions of the transposed matrix, and the transposed matrix itself
+    fn transpose(&self) -> &(HashMap<MinKey, usize>, CSM<usize, SnzVal>) {
+        self.transpose.get_or_init(|| {
+            let csm = &self.csm;
+
+            // count the entries of each minor field
+            let mut minkey_2_index = HashMap::new();
+            let mut counts = Vec::new();
+            for minkey in csm.minind.iter() {
+                let index = *minkey_2_index.entry(minkey.clone()).or_insert_with(|| { counts.push(0); counts.len() - 1 });
+                counts[index] += 1;
+            }
+
+            // the pointers of the transposed matrix
+            let mut majptr = Vec::with_capacity(counts.len() + 1);
+            majptr.push(0);
+            for count in counts.iter() {
+                majptr.push(majptr[majptr.len() - 1] + count);
+            }
+
+            // place each entry, visiting major fields in order so that each minor field is sorted by major index
+            let mut next = majptr.clone();
+            let mut minind = vec![0; csm.minind.len()];
+            let mut snzval: Vec<Option<SnzVal>> = vec![None; csm.snzval.len()];
+            for majind in 0..csm.nummaj {
+                for ii in csm.majptr[majind]..csm.majptr[majind+1] {
+                    let index = minkey_2_index[&csm.minind[ii]];
+                    minind[next[index]] = majind;
+                    snzval[next[index]] = Some(csm.snzval[ii].clone());
+                    next[index] += 1;
+                }
+            }
+
+            let majdim = match csm.majdim {
+                MajorDimension::Row => MajorDimension::Col,
+                MajorDimension::Col => MajorDimension::Row
+            };
+            let transposed = CSM {
+                ringmetadata: csm.ringmetadata.clone(),
+                nummaj: counts.len(),
+                majdim,
+                majptr,
+                minind,
+                snzval: snzval.into_iter().map(|val| val.unwrap()).collect(),
+                minkey_order: Some(usize::cmp)
+            };
+            (minkey_2_index, transposed)
+        })
+    }
+}
+
+// See matrix.rs file for specific definition of SmOracle trait
+impl<MinKey, SnzVal> SmOracle<usize, MinKey, SnzVal> for DualCsm<MinKey, SnzVal> where
+MinKey: Clone + PartialEq + Eq + Hash,
+SnzVal: Clone
+{
+    fn ring( &self ) -> &RingMetadata<SnzVal> {
+        &self.csm.ringmetadata
+    }
+
+    fn maj_dim( &self ) -> MajorDimension { self.csm.majdim.clone() }
+
+    fn maj_itr( &self, majkey: &usize ) -> Box<dyn Iterator<Item=(MinKey, SnzVal)> + '_> {
+        self.csm.maj_itr(majkey)
+    }
+
+    fn min_itr( &self, minkey: &MinKey ) -> Box<dyn Iterator<Item=(usize, SnzVal)> + '_> {
+        let (minkey_2_index, transposed) = self.transpose();
+        match minkey_2_index.get(minkey) {
+            Some(index) => transposed.maj_itr(index),
+            None => Box::new(std::iter::empty())
+        }
+    }
+
+    fn entry( &self, majkey: &usize, minkey: &MinKey ) -> Option<SnzVal> {
+        self.csm.entry(majkey, minkey)
+    }
+
+    fn maj_length( &self, majkey: &usize ) -> usize {
+        self.csm.majptr[*majkey+1] - self.csm.majptr[*majkey]
+    }
+
+    fn min_length( &self, minkey: &MinKey ) -> usize {
+        let (minkey_2_index, transposed) = self.transpose();
+        match minkey_2_index.get(minkey) {
+            Some(index) => transposed.majptr[*index+1] - transposed.majptr[*index],
+            None => 0
+        }
+    }
+
+    fn countsnz( &self ) -> Option<usize> {
+        self.csm.countsnz()
+    }
+
+    fn maj_itr_sorted( &self ) -> bool {
+        self.csm.maj_itr_sorted()
+    }
+
+    fn min_itr_sorted( &self ) -> bool {
+        true
+    }
+
+    fn finiteminors( &self ) -> Option<bool> {
+        Some(true)
+    }
+
+    fn finitemajors( &self ) -> Option<bool> {
+        Some(true)
+    }
+}
@@ -22,12 +22,11 @@ Example:
 */
 
 
-use std::ops::Neg;
 use std::convert::TryInto;
 use std::hash::Hash;
 use std::fmt::Debug;
 
-use crate::matrix::{SmOracle, RingMetadata, MajorDimension};
+use crate::matrix::{SmOracle, RingMetadata, RingElement, Ring, MajorDimension};
 use crate::chx::{ChainComplex, ChxTransformKind};
 
 type Coordi = u32;
@@ -127,7 +126,7 @@ struct CofacetIter<'a, FilVal: Clone, SnzVal: Clone> {
 /// implement standard methods of Iterator for CofacetIter struct
 impl<'a, FilVal, SnzVal> Iterator for CofacetIter<'a, FilVal, SnzVal> where
 FilVal: Clone + Debug + PartialOrd,
-SnzVal: Clone + Neg<Output = SnzVal>
+SnzVal: RingElement
 {
 	type Item = (Cube<FilVal>, SnzVal);
 
@@ -143,7 +142,7 @@ SnzVal: Clone + Neg<Output = SnzVal>
         }
 
         let mut coordinates = self.current_cube_coordinates.clone();
-        let mut coeff = -self.one.clone();
+        let mut coeff = self.cubical.ringmetadata.negate(&self.one);
         if self.just_arrived {
             if coordinates[self.edit_location] == 0 {
                 coordinates[self.edit_location] += 1;
@@ -197,7 +196,7 @@ struct FacetIter<'a, FilVal: Clone + Debug, SnzVal: Clone> {
 /// implement standard methods of Iterator for FacetIter struct
 impl<FilVal, SnzVal> Iterator for FacetIter<'_, FilVal, SnzVal> where
 FilVal: Clone + PartialOrd + Debug + Ord,
-SnzVal: Clone + Neg<Output = SnzVal>
+SnzVal: RingElement
 {
 	type Item = (Cube<FilVal>, SnzVal);
 
@@ -213,7 +212,7 @@ SnzVal: Clone + Neg<Output = SnzVal>
         }
 
         let mut coordinates = self.current_cube_coordinates.clone();
-        let mut coeff = -self.one.clone();
+        let mut coeff = self.cubical.ringmetadata.negate(&self.one);
         if self.just_arrived {
                 coordinates[self.edit_location] -= 1;
                 self.just_arrived = false;
@@ -239,13 +238,15 @@ SnzVal: Clone + Neg<Output = SnzVal>
 // See matrix.rs file for specific definition of SmOracle trait
 impl<FilVal, SnzVal> SmOracle<Cube<FilVal>, Cube<FilVal>, SnzVal> for CubicalBoundaryMatrix<FilVal, SnzVal> where
 FilVal: PartialOrd + Clone + Debug + Eq + Hash + Ord,
-SnzVal: Neg<Output = SnzVal> + Clone
+SnzVal: RingElement
 {
 
     fn ring(&self) -> &RingMetadata<SnzVal> {
         &self.ringmetadata
     }
 
+	fn maj_dim( &self ) -> MajorDimension { self.majordimension.clone() }
+
 	fn maj_itr(&self, majkey: &Cube<FilVal>) -> Box<dyn Iterator<Item=(Cube<FilVal>, SnzVal)> + '_> {
 
         if self.majordimension == MajorDimension::Row {
@@ -447,7 +448,7 @@ SnzVal: Clone
 
 // Implimentation of ChainComplex trait. See chx.rs for definition of ChainComplex trait.
 impl<SnzVal, FilVal> ChainComplex<Cube<FilVal>, SnzVal, FilVal> for CubicalComplex<SnzVal, FilVal> where
-SnzVal: Clone + Neg<Output = SnzVal> + Debug,
+SnzVal: RingElement + Debug,
 FilVal: PartialOrd + Copy + Debug + Eq + Hash + Ord
 {
     type Matrix = CubicalBoundaryMatrix<FilVal, SnzVal>;
@@ -19,11 +19,10 @@ use std::collections::BinaryHeap;
 use std::hash::Hash;
 use std::fmt::Debug;
 use std::collections::HashMap;
-use std::ops::{Add, Neg, AddAssign, Mul};
 
-use crate::matrix::{SmOracle, InvMod, MajorDimension, RingMetadata};
+use crate::matrix::{SmOracle, RingElement, Semiring, Ring, DivisionRing, MajorDimension, RingMetadata, DecompResult};
 use crate::csm::CSM;
-use crate::solver::multiply_hash_smoracle_version2;
+use crate::solver::{multiply_hash_smoracle_version2, multiply_support_smoracle_gf2, symmetric_difference_sorted};
 use crate::chx::Indexing;
 
 /// Scale a given row and add it to a sparse vector (represented as a hash table  associated with a binary heap which record the priorities of each entry)
@@ -41,12 +40,12 @@ pub fn update_heap_hash<MinKey, SnzVal>(
     row:            &mut HashMap<MinKey, SnzVal>,
     scale: &SnzVal) where
 MinKey: Eq + Hash + Ord + Clone,
-SnzVal: Clone + AddAssign + Mul<Output = SnzVal> + PartialEq + InvMod<Output = SnzVal>
+SnzVal: RingElement
 {
     for (key, val) in row.drain() {
-        let value = scale.clone()*val;
+        let value = ringmetadata.multiply(scale, &val);
         if let Some(x) = hash.get_mut(&key) {
-            *x += value;
+            *x = ringmetadata.add(x, &value);
             if ringmetadata.is_0(x) {
                 hash.remove(&key);
             }
@@ -63,18 +62,29 @@ SnzVal: Clone + AddAssign + Mul<Output = SnzVal> + PartialEq + InvMod<Output = S
 /// - `matrix`: a sparse matrix oracle
 /// - `maj_to_reduce`: an vector indicates the rows(majs) and the order to perform decomposition
 ///
+/// When `matrix.ring().is_binary()`, each vector is stored as the sorted list of keys where it is nonzero, and row additions are carried out as symmetric differences of these lists, with no multiplications or inversions.
+///
 /// # Returns
 /// - A sparse matrix in compressed row format representing the row operation.
 /// - A Indexing recording pivot matrix, order of pivot major indices, order of pivot minor indices.
+///
+/// # Errors
+/// [`DecompError::NotAField`](crate::matrix::DecompError::NotAField) if the coefficient ring is not a field, and [`DecompError::UnsupportedRing`](crate::matrix::DecompError::UnsupportedRing) if the coefficient type cannot represent it (for example, `Modulus(n)` with `n` too large for the type); both are checked before any elimination is done.
 pub fn decomp_col<MajKey, MinKey, SnzVal, Matrix>(
     matrix:             &Matrix,
     maj_to_reduce:      &mut Vec<MajKey>,
-) -> (CSM<usize, SnzVal>, Indexing<MinKey, MajKey>) where
+) -> DecompResult<MinKey, MajKey, SnzVal> where
 MajKey: PartialEq + Eq + Hash + Clone + Ord + Debug,
 MinKey: PartialEq + Eq + Hash + Clone + Ord + Debug,
-SnzVal: Add + Neg<Output=SnzVal> +Clone + PartialEq + InvMod<Output=SnzVal> + Mul<Output=SnzVal> + AddAssign + Debug,
+SnzVal: RingElement + Debug,
 Matrix: SmOracle<MajKey, MinKey, SnzVal>
 {
+    matrix.ring().check_field()?;
+
+    if matrix.ring().is_binary() {
+        return Ok(decomp_col_gf2(matrix, maj_to_reduce));
+    }
+
     //initialize "majoper" and "indexing"
     let length:f64 = maj_to_reduce.len() as f64;
     let capacity:usize = (length*1.2) as usize;
@@ -118,12 +128,12 @@ Matrix: SmOracle<MajKey, MinKey, SnzVal>
                     row_reduced = multiply_hash_smoracle_version2(&row_majoper, &indexing.index_2_majkey, matrix);
 
                     if let Some(dominator) = row_reduced.remove(&minkey) {
-                        if let Some(inverse) = matrix.ring().inverse(&dominator){
-                            let mut scale = -leading_entry*inverse;
-                            scale = matrix.ring().simplify(&scale);
-                            update_heap_hash(&matrix.ring(), &mut heap_reduced, &mut hash_reduced, &mut row_reduced, &scale);
-                            update_heap_hash(&matrix.ring(), &mut heap_majoper, &mut hash_majoper, &mut row_majoper, &scale);
-                        }
+                        let inverse = matrix.ring().inverse(&dominator)
+                            .expect("the ring is a field, so every nonzero pivot is invertible");
+                        let mut scale = matrix.ring().multiply(&matrix.ring().negate(&leading_entry), &inverse);
+                        scale = matrix.ring().simplify(&scale);
+                        update_heap_hash(&matrix.ring(), &mut heap_reduced, &mut hash_reduced, &mut row_reduced, &scale);
+                        update_heap_hash(&matrix.ring(), &mut heap_majoper, &mut hash_majoper, &mut row_majoper, &scale);
                     }
                 } else {
                     indexing.minkey_2_index.insert(minkey.clone(), majoper.nummaj);
@@ -150,5 +160,70 @@ Matrix: SmOracle<MajKey, MinKey, SnzVal>
 
     majoper.shrink_to_fit();
     indexing.shrink_to_fit();
-    return (majoper, indexing);
+    Ok((majoper, indexing))
+}
+
+/// The body of [`decomp_col`](decomp_col) over Z/2Z
+///
+/// As in the Z/2Z path of [`decomp_row`](crate::decomp_row::decomp_row), each vector is stored as the sorted list of keys
+/// where it is nonzero; the leading entry of a column is the last key of its list.
+fn decomp_col_gf2<MajKey, MinKey, SnzVal, Matrix>(
+    matrix:             &Matrix,
+    maj_to_reduce:      &mut Vec<MajKey>,
+) -> (CSM<usize, SnzVal>, Indexing<MinKey, MajKey>) where
+MajKey: PartialEq + Eq + Hash + Clone + Ord + Debug,
+MinKey: PartialEq + Eq + Hash + Clone + Ord + Debug,
+SnzVal: RingElement + Debug,
+Matrix: SmOracle<MajKey, MinKey, SnzVal>
+{
+    let capacity: usize = (maj_to_reduce.len() as f64 * 1.2) as usize;
+    // every column of `majoper` is sorted, since its own index comes last and is the largest
+    let mut majoper: CSM<usize, SnzVal> = CSM::with_capacity_sorted(capacity, MajorDimension::Col, matrix.ring().clone());
+    let mut indexing = Indexing::with_capacity(capacity);
+    let one: SnzVal = matrix.ring().identity_multiplicative.clone();
+
+    //eliminate cols
+    while let Some(majkey) = maj_to_reduce.pop() {
+
+        let mut support_reduced: Vec<MinKey> = matrix.maj_itr(&majkey)
+            .filter(|(_, val)| !matrix.ring().is_0(val))
+            .map(|(key, _)| key)
+            .collect();
+        support_reduced.sort();
+        let mut support_majoper: Vec<usize> = Vec::new();
+
+        while let Some(minkey) = support_reduced.last().cloned() {
+            match indexing.minkey_2_index.get(&minkey) {
+                Some(index) => {
+                    let col_majoper = &majoper.minind[majoper.majptr[*index]..majoper.majptr[*index + 1]];
+                    let col_reduced = multiply_support_smoracle_gf2(col_majoper, &indexing.index_2_majkey, matrix);
+                    support_reduced = symmetric_difference_sorted(std::mem::take(&mut support_reduced), col_reduced);
+                    support_majoper = symmetric_difference_sorted(std::mem::take(&mut support_majoper), col_majoper.to_vec());
+                }
+                None => {
+                    indexing.minkey_2_index.insert(minkey.clone(), majoper.nummaj);
+                    indexing.majkey_2_index.insert(majkey.clone(), majoper.nummaj);
+                    indexing.index_2_majkey.push(majkey);
+                    indexing.index_2_minkey.push(minkey);
+
+                    for index in support_majoper.iter() {
+                        majoper.push_snzval(*index, one.clone());
+                    }
+                    majoper.push_snzval(majoper.nummaj, one.clone());
+                    majoper.majptr.push(majoper.minind.len());
+                    majoper.nummaj += 1;
+                    break;
+                }
+            }
+        }
+    }
+
+    // Order minkeys from largest to smallest, as the heap in `decomp_col` does
+    let mut ordered_minind: Vec<usize> = (0..indexing.index_2_minkey.len()).collect();
+    ordered_minind.sort_by(|a, b| indexing.index_2_minkey[*b].cmp(&indexing.index_2_minkey[*a]));
+    indexing.ordered_minind = ordered_minind;
+
+    majoper.shrink_to_fit();
+    indexing.shrink_to_fit();
+    (majoper, indexing)
 }
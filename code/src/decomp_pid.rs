@@ -0,0 +1,286 @@
+/*!
+
+Persistent homology over a principal ideal domain
+
+# Reduction without division by non-units
+
+The routines in [`decomp_row`](crate::decomp_row) and [`decomp_col`](crate::decomp_col) divide by
+pivot entries, so they only make sense when every pivot is invertible (e.g. over a field).  Over the
+integers this fails as soon as a boundary matrix has an entry like 2.
+
+The function [`decomp_pid`](decomp_pid) reduces the major fields of a matrix one at a time, as in
+the standard column algorithm, but only ever adds *integer* multiples of one field to another.  When
+the leading entry `b` of the field being reduced is not divisible by the pivot `a` already sitting in
+the same minor position, the two fields are replaced by a unimodular combination: one with leading
+entry `gcd(a, b)`, which becomes the new pivot, and one whose leading entry is zero, which we
+continue to reduce.  The pivot in each minor position therefore only ever shrinks (in the divisibility
+order), and the sequence of values it takes records how the corresponding homology class acquires
+torsion before it dies.
+
+For a chain complex, [`barcode_pid`](barcode_pid) turns this into a barcode in which each bar comes with
+its torsion coefficients.  For example, a bar with `torsion = [(t, 2)]` and `death = Some(s)` is a class
+born at `birth` which has order 2 on the interval `[t, s)`, and is zero from `s` onward.
+
+```
+use exhact::matrix::{RingSpec, RingMetadata, MajorDimension};
+use exhact::csm::CSM;
+use exhact::decomp_pid::decomp_pid;
+
+// a column-major matrix over the integers with a single row:
+//
+// 4 6 1
+let ringmetadata = RingMetadata{
+    ringspec: RingSpec::Integer,
+    identity_additive: 0i64,
+    identity_multiplicative: 1i64,
+};
+let mut matrix = CSM::new(MajorDimension::Col, ringmetadata);
+matrix.append_maj(&mut vec![(0, 4)].into_iter().collect());
+matrix.append_maj(&mut vec![(0, 6)].into_iter().collect());
+matrix.append_maj(&mut vec![(0, 1)].into_iter().collect());
+
+// columns are popped from the end of the vector, so list them in descending order
+let mut maj_to_reduce = vec![2, 1, 0];
+let reduction = decomp_pid(&matrix, &mut maj_to_reduce);
+
+// the pivot in row 0 is 4, then gcd(4, 6) = 2, then 1
+assert_eq!(reduction.pivot_history[&0], vec![(0, 4), (1, 2), (2, 1)]);
+// after the gcd step, what is left of column 1 is zero; column 2 is eliminated outright
+assert_eq!(reduction.zero_majkeys, vec![1, 2]);
+```
+
+*/
+
+use std::collections::BinaryHeap;
+use std::hash::Hash;
+use std::fmt::Debug;
+use std::collections::HashMap;
+
+use crate::matrix::{SmOracle, EuclideanElement, EuclideanRing, DivisionRing, Ring, Semiring, MajorDimension, RingMetadata};
+use crate::chx::{ChainComplex, ChxTransformKind};
+use crate::decomp_col::update_heap_hash;
+use crate::solver::add_assign_hash;
+
+/// The result of reducing a matrix over a principal ideal domain with [`decomp_pid`](decomp_pid)
+#[derive(Clone, Debug)]
+pub struct PidReduction<MajKey, MinKey, SnzVal> where
+MinKey: Eq + Hash
+{
+    /// For each minor key that has ever held a pivot, the list of pairs `(majkey, coefficient)` such that,
+    /// once the major field `majkey` has been reduced, the (normalized) pivot in this minor position is
+    /// `coefficient`.  The pivot becomes a unit if and only if the last coefficient is a unit.
+    pub pivot_history: HashMap<MinKey, Vec<(MajKey, SnzVal)>>,
+    /// The major keys whose fields reduce to zero, in the order they were processed
+    pub zero_majkeys: Vec<MajKey>,
+}
+
+/// The linear combination `x_scale * x + y_scale * y` of two sparse vectors
+fn linear_combination<Key, SnzVal>(
+    ringmetadata:   &RingMetadata<SnzVal>,
+    x:              &HashMap<Key, SnzVal>,
+    x_scale:        &SnzVal,
+    y:              &HashMap<Key, SnzVal>,
+    y_scale:        &SnzVal
+) -> HashMap<Key, SnzVal> where
+Key: Eq + Hash + Ord + Clone,
+SnzVal: EuclideanElement
+{
+    let mut combination = HashMap::new();
+    add_assign_hash(ringmetadata, &mut combination, &mut x.clone(), x_scale);
+    add_assign_hash(ringmetadata, &mut combination, &mut y.clone(), y_scale);
+    combination
+}
+
+/// Reduce a sparse matrix over a principal ideal domain, without dividing by non-units
+///
+/// The leading entry of a major field is its entry with the *largest* minor key, as in
+/// [`decomp_col`](crate::decomp_col::decomp_col).
+///
+/// # Parameters
+/// - `matrix`: a sparse matrix oracle whose coefficients support division with remainder
+/// - `maj_to_reduce`: the major keys to reduce; keys are popped from the end of the vector, so it should be sorted in descending order
+///
+/// # Returns
+/// A [`PidReduction`](PidReduction) recording how the pivot in each minor position evolves, and which major fields reduce to zero.
+pub fn decomp_pid<MajKey, MinKey, SnzVal, Matrix>(
+    matrix:             &Matrix,
+    maj_to_reduce:      &mut Vec<MajKey>,
+) -> PidReduction<MajKey, MinKey, SnzVal> where
+MajKey: PartialEq + Eq + Hash + Clone + Ord + Debug,
+MinKey: PartialEq + Eq + Hash + Clone + Ord + Debug,
+SnzVal: EuclideanElement + Debug,
+Matrix: SmOracle<MajKey, MinKey, SnzVal>
+{
+    let ring = matrix.ring();
+    let mut reduction = PidReduction{ pivot_history: HashMap::new(), zero_majkeys: Vec::new() };
+
+    // the current pivot fields, indexed by the minor key of their leading entry
+    let mut pivots: HashMap<MinKey, HashMap<MinKey, SnzVal>> = HashMap::new();
+
+    let mut heap_reduced = BinaryHeap::new();
+    let mut hash_reduced = HashMap::new();
+
+    while let Some(majkey) = maj_to_reduce.pop() {
+
+        heap_reduced.clear();
+        hash_reduced.clear();
+        for (key, val) in matrix.maj_itr(&majkey) {
+            heap_reduced.push(key.clone());
+            hash_reduced.insert(key, val);
+        }
+
+        let mut reduces_to_zero = true;
+        while let Some(minkey) = heap_reduced.pop() {
+            let leading_entry = match hash_reduced.remove(&minkey) {
+                Some(val) => val,
+                None => continue
+            };
+            if ring.is_0(&leading_entry) { continue; }
+
+            let pivot_field = match pivots.get_mut(&minkey) {
+                Some(field) => field,
+                None => {
+                    // a new pivot
+                    hash_reduced.insert(minkey.clone(), leading_entry.clone());
+                    reduction.pivot_history.entry(minkey.clone()).or_insert_with(Vec::new)
+                        .push((majkey.clone(), ring.normalize(&leading_entry)));
+                    pivots.insert(minkey, std::mem::take(&mut hash_reduced));
+                    reduces_to_zero = false;
+                    break;
+                }
+            };
+
+            let pivot_entry = pivot_field[&minkey].clone();
+            let (quotient, remainder) = ring.divide_with_remainder(&leading_entry, &pivot_entry);
+            if ring.is_0(&remainder) {
+                // the pivot divides the leading entry; clear it with an integer multiple of the pivot field
+                let mut field = pivot_field.clone();
+                field.remove(&minkey);
+                update_heap_hash(ring, &mut heap_reduced, &mut hash_reduced, &mut field, &ring.negate(&quotient));
+            } else {
+                // replace the pair of fields with a unimodular combination: one has leading entry
+                // gcd(pivot_entry, leading_entry) and becomes the new pivot; the other has zero in
+                // this position, and we continue to reduce it
+                let (gcd, s, t) = ring.gcd_bezout(&pivot_entry, &leading_entry);
+                let pivot_cofactor = ring.divide_with_remainder(&pivot_entry, &gcd).0;
+                let leading_cofactor = ring.divide_with_remainder(&leading_entry, &gcd).0;
+                hash_reduced.insert(minkey.clone(), leading_entry);
+
+                let new_pivot = linear_combination(ring, pivot_field, &s, &hash_reduced, &t);
+                let mut residual = linear_combination(ring, pivot_field, &leading_cofactor, &hash_reduced, &ring.negate(&pivot_cofactor));
+                residual.remove(&minkey);
+
+                *pivot_field = new_pivot;
+                reduction.pivot_history.get_mut(&minkey).unwrap().push((majkey.clone(), gcd));
+
+                hash_reduced = residual;
+                heap_reduced.clear();
+                heap_reduced.extend(hash_reduced.keys().cloned());
+            }
+        }
+
+        if reduces_to_zero {
+            reduction.zero_majkeys.push(majkey);
+        }
+    }
+
+    reduction
+}
+
+/// A persistence bar with torsion coefficients, computed over a principal ideal domain
+#[derive(Clone, Debug, PartialEq)]
+pub struct TorsionBar<Filtration, SnzVal> {
+    /// The filtration value at which the class is born
+    pub birth: Filtration,
+    /// The filtration value at which the class becomes zero, or `None` if it never does
+    pub death: Option<Filtration>,
+    /// The pairs `(filtration, coefficient)` at which the order of the class changes, in increasing order;
+    /// from `filtration` onward (until the next pair, or `death`) the class has order `coefficient`
+    pub torsion: Vec<(Filtration, SnzVal)>,
+}
+
+/// Returns the persistence barcode of a chain complex in a given homological degree, with torsion coefficients
+///
+/// Coefficients are taken in the ring of the chain complex, which must support division with remainder
+/// (e.g. `RingSpec::Integer`).  Over a field every pivot is a unit, so there is no torsion and the result
+/// agrees with [`FactoredComplexBlockCsm::barcode`](crate::chx::FactoredComplexBlockCsm::barcode).
+/// As in that method, bars of length zero are omitted.
+///
+/// The chain complex must be able to build boundaries in degrees `h_degree` and `h_degree + 1`.
+///
+/// ```
+/// use exhact::matrix::{RingSpec, RingMetadata, MajorDimension};
+/// use exhact::cubical::CubicalComplex;
+/// use exhact::decomp_pid::{barcode_pid, TorsionBar};
+///
+/// // a 3 x 3 image whose middle pixel enters last: a loop is born at 1 and filled in at 5
+/// let chx = CubicalComplex {
+///     ringmetadata: RingMetadata{
+///         ringspec: RingSpec::Integer,
+///         identity_additive: 0i64,
+///         identity_multiplicative: 1i64,
+///     },
+///     shape: vec![3, 3],
+///     entry_array: vec![ 1, 1, 1,
+///                        1, 5, 1,
+///                        1, 1, 1 ],
+///     max_value: 5,
+///     safe_homology_degrees_to_build_boundaries: vec![1, 2],
+///     major_dimension: MajorDimension::Col,
+/// };
+///
+/// // cubical complexes in the plane have no torsion, so every bar has an empty torsion list
+/// assert_eq!(barcode_pid(&chx, 0), vec![ TorsionBar{ birth: 1, death: None, torsion: vec![] } ]);
+/// assert_eq!(barcode_pid(&chx, 1), vec![ TorsionBar{ birth: 1, death: Some(5), torsion: vec![] } ]);
+/// assert_eq!(barcode_pid(&chx, 2), vec![]);
+/// ```
+pub fn barcode_pid<MatrixIndexKey, SnzVal, Filtration, OriginalChx>(
+    original_complex:   &OriginalChx,
+    h_degree:           usize
+) -> Vec<TorsionBar<Filtration, SnzVal>> where
+MatrixIndexKey: PartialEq + Eq + Ord + Hash + Clone + Debug,
+SnzVal: EuclideanElement + Debug,
+Filtration: PartialOrd + Clone,
+OriginalChx: ChainComplex<MatrixIndexKey, SnzVal, Filtration>
+{
+    let matrix = original_complex.get_smoracle(
+        MajorDimension::Col,
+        ChxTransformKind::Boundary
+    );
+
+    // keys of degree h_degree whose boundaries reduce to zero (every vertex is a cycle)
+    let mut keys = original_complex.keys_ordered(h_degree);
+    let cycles = if h_degree == 0 {
+        keys
+    } else {
+        keys.reverse();
+        decomp_pid(&matrix, &mut keys).zero_majkeys
+    };
+
+    let mut keys_upper = original_complex.keys_ordered(h_degree + 1);
+    keys_upper.reverse();
+    let reduction = decomp_pid(&matrix, &mut keys_upper);
+
+    let mut barcode = Vec::new();
+    for key in cycles {
+        let birth = original_complex.key_2_filtration(&key);
+        let mut bar = TorsionBar{ birth: birth.clone(), death: None, torsion: Vec::new() };
+        if let Some(history) = reduction.pivot_history.get(&key) {
+            for (majkey, coefficient) in history {
+                let filtration = original_complex.key_2_filtration(majkey);
+                // only the last change at any given filtration value is visible
+                if bar.torsion.last().is_some_and(|(f, _)| *f == filtration) {
+                    bar.torsion.pop();
+                }
+                if matrix.ring().inverse(coefficient).is_some() {
+                    bar.death = Some(filtration);
+                    break;
+                }
+                bar.torsion.push((filtration, coefficient.clone()));
+            }
+        }
+        if bar.torsion.is_empty() && bar.death.as_ref() == Some(&birth) { continue; }
+        barcode.push(bar);
+    }
+    barcode
+}
@@ -18,11 +18,10 @@ use std::cmp::Reverse;
 use std::hash::Hash;
 use std::fmt::Debug;
 use std::collections::HashMap;
-use std::ops::{Add, Neg, AddAssign, Mul};
 
-use crate::matrix::{SmOracle, InvMod, MajorDimension, RingMetadata};
+use crate::matrix::{SmOracle, RingElement, Semiring, Ring, DivisionRing, MajorDimension, RingMetadata, DecompResult, DecompResultWith};
 use crate::csm::CSM;
-use crate::solver::multiply_hash_smoracle_version2;
+use crate::solver::{multiply_hash_smoracle_version2, multiply_support_smoracle_gf2, symmetric_difference_sorted};
 use crate::chx::Indexing;
 
 /// Scale a given row and add it to a sparse vector (represented as a hash table  associated with a binary heap which record the priorities of each entry)
@@ -40,12 +39,12 @@ pub fn update_heap_hash<MinKey, SnzVal>(
     row:            &mut HashMap<MinKey, SnzVal>,
     scale: &SnzVal) where
 MinKey: Eq + Hash + Ord + Clone,
-SnzVal: Clone + AddAssign + Mul<Output = SnzVal> + PartialEq + InvMod<Output = SnzVal>
+SnzVal: RingElement
 {
     for (key, val) in row.drain() {
-        let value = scale.clone()*val;
+        let value = ringmetadata.multiply(scale, &val);
         if let Some(x) = hash.get_mut(&key) {
-            *x += value;
+            *x = ringmetadata.add(x, &value);
             if ringmetadata.is_0(x) {
                 hash.remove(&key);
             }
@@ -57,23 +56,132 @@ SnzVal: Clone + AddAssign + Mul<Output = SnzVal> + PartialEq + InvMod<Output = S
     }
 }
 
+/// The pivot threshold used by [`decomp_row`](decomp_row); see [`decomp_row_with_pivot_threshold`](decomp_row_with_pivot_threshold).
+pub const DEFAULT_PIVOT_THRESHOLD: f64 = 1e-8;
+
+/// A summary of the pivots used by [`decomp_row_with_pivot_threshold`](decomp_row_with_pivot_threshold)
+///
+/// Both fields are only filled in for coefficients with a magnitude (e.g. `f64` with `RingSpec::Float(epsilon)`); for exact coefficient types the report is always the default.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct PivotReport {
+    /// The number of pivots with absolute value below the pivot threshold
+    pub num_small_pivots: usize,
+    /// The smallest absolute value of any pivot, or `None` if no pivot has a magnitude
+    pub smallest_pivot: Option<f64>,
+}
+
 /// UU decompose of a sparse matrix
 /// # Parameters
 /// - `matrix`: a sparse matrix oracle
 /// - `maj_to_reduce`: an vector indicates the rows(majs) and the order to perform decomposition
 ///
+/// When `matrix.ring().is_binary()`, each vector is stored as the sorted list of keys where it is nonzero, and row additions are carried out as symmetric differences of these lists, with no multiplications or inversions.
+///
+/// For floating point coefficients this uses [`DEFAULT_PIVOT_THRESHOLD`](DEFAULT_PIVOT_THRESHOLD) and discards the resulting
+/// [`PivotReport`](PivotReport), so no warning about small pivots is ever given.  Callers who need that warning must call
+/// [`decomp_row_with_pivot_threshold`](decomp_row_with_pivot_threshold) instead.
+///
 /// # Returns
 /// - A sparse matrix in compressed row format representing the row operation.
 /// - A Indexing recording pivot matrix, order of pivot major indices, order of pivot minor indices.
+///
+/// # Errors
+/// [`DecompError::NotAField`](crate::matrix::DecompError::NotAField) if the coefficient ring is not a field, and [`DecompError::UnsupportedRing`](crate::matrix::DecompError::UnsupportedRing) if the coefficient type cannot represent it (for example, `Modulus(n)` with `n` too large for the type); both are checked before any elimination is done.
+///
+/// ```
+/// use exhact::matrix::{RingSpec, RingMetadata, MajorDimension, Z2Bool};
+/// use exhact::csm::CSM;
+/// use exhact::decomp_row::decomp_row;
+///
+/// // a 3x3 matrix over Z/2 whose rows sum to zero
+/// //
+/// // [ 1 1 0 ]
+/// // [ 0 1 1 ]
+/// // [ 1 0 1 ]
+/// let ringmetadata = RingMetadata{ ringspec: RingSpec::Modulus(2), identity_additive: 0i16, identity_multiplicative: 1i16 };
+/// let mut matrix = CSM::new(MajorDimension::Row, ringmetadata);
+/// matrix.append_maj(&mut vec![(0, 1), (1, 1)].into_iter().collect());
+/// matrix.append_maj(&mut vec![(1, 1), (2, 1)].into_iter().collect());
+/// matrix.append_maj(&mut vec![(0, 1), (2, 1)].into_iter().collect());
+///
+/// // rows are reduced from last to first: row 2 is matched to column 0, row 1 to column 1,
+/// // and row 0 is the sum of the other two, so it reduces to zero
+/// let (rowoper, indexing) = decomp_row(&matrix, &mut vec![0, 1, 2]).unwrap();
+/// assert_eq!(indexing.index_2_majkey, vec![2, 1]);
+/// assert_eq!(indexing.index_2_minkey, vec![0, 1]);
+/// assert_eq!(rowoper.nummaj, 2);
+///
+/// // the same matrix with one-byte coefficients gives the same pairs
+/// let ringmetadata = RingMetadata{ ringspec: RingSpec::Modulus(2), identity_additive: Z2Bool(false), identity_multiplicative: Z2Bool(true) };
+/// let mut matrix = CSM::new(MajorDimension::Row, ringmetadata);
+/// matrix.append_maj(&mut vec![(0, Z2Bool(true)), (1, Z2Bool(true))].into_iter().collect());
+/// matrix.append_maj(&mut vec![(1, Z2Bool(true)), (2, Z2Bool(true))].into_iter().collect());
+/// matrix.append_maj(&mut vec![(0, Z2Bool(true)), (2, Z2Bool(true))].into_iter().collect());
+/// let (_, indexing) = decomp_row(&matrix, &mut vec![0, 1, 2]).unwrap();
+/// assert_eq!(indexing.index_2_majkey, vec![2, 1]);
+/// assert_eq!(indexing.index_2_minkey, vec![0, 1]);
+/// ```
 pub fn decomp_row<MajKey, MinKey, SnzVal, Matrix>(
     matrix:             &Matrix,
     maj_to_reduce:      &mut Vec<MajKey>,
-) -> (CSM<usize, SnzVal>, Indexing<MinKey, MajKey>) where
+) -> DecompResult<MinKey, MajKey, SnzVal> where
+MajKey: PartialEq + Eq + Hash + Clone + Ord + Debug,
+MinKey: PartialEq + Eq + Hash + Clone + Ord + Debug,
+SnzVal: RingElement + Debug,
+Matrix: SmOracle<MajKey, MinKey, SnzVal>
+{
+    decomp_row_with_pivot_threshold(matrix, maj_to_reduce, DEFAULT_PIVOT_THRESHOLD)
+        .map(|(rowoper, indexing, _)| (rowoper, indexing))
+}
+
+/// UU decompose of a sparse matrix, with a report on small pivots
+///
+/// Same as [`decomp_row`](decomp_row).  In addition, for coefficients with a magnitude (e.g. `f64` with `RingSpec::Float(epsilon)`),
+/// every pivot whose absolute value is below `pivot_threshold` is counted; dividing by such pivots amplifies rounding error, so
+/// callers may want to warn when the count is nonzero.  Entries with absolute value at most `epsilon` are never used as pivots.
+/// Exact coefficient types are not affected.
+///
+/// # Parameters
+/// - `matrix`: a sparse matrix oracle
+/// - `maj_to_reduce`: an vector indicates the rows(majs) and the order to perform decomposition
+/// - `pivot_threshold`: pivots with absolute value below this number are counted as numerically unstable
+///
+/// # Returns
+/// The same as [`decomp_row`](decomp_row), together with a [`PivotReport`](PivotReport).
+///
+/// ```
+/// use exhact::matrix::{RingSpec, RingMetadata, MajorDimension};
+/// use exhact::csm::CSM;
+/// use exhact::decomp_row::{decomp_row_with_pivot_threshold, PivotReport};
+///
+/// let ringmetadata = RingMetadata{ ringspec: RingSpec::Float(1e-12), identity_additive: 0.0, identity_multiplicative: 1.0 };
+/// let mut matrix: CSM<usize, f64> = CSM::new(MajorDimension::Row, ringmetadata);
+/// matrix.append_maj(&mut vec![(0, 1e-10), (1, 1.0)].into_iter().collect());
+/// matrix.append_maj(&mut vec![(1, 2.0)].into_iter().collect());
+///
+/// let (_rowoper, _indexing, report) = decomp_row_with_pivot_threshold(&matrix, &mut vec![1, 0], 1e-8).unwrap();
+/// assert_eq!(report, PivotReport{ num_small_pivots: 1, smallest_pivot: Some(1e-10) });
+/// if report.num_small_pivots > 0 {
+///     eprintln!("Warning: {} pivot(s) below 1e-8; the elimination may be numerically unstable.", report.num_small_pivots);
+/// }
+/// ```
+pub fn decomp_row_with_pivot_threshold<MajKey, MinKey, SnzVal, Matrix>(
+    matrix:             &Matrix,
+    maj_to_reduce:      &mut Vec<MajKey>,
+    pivot_threshold:    f64,
+) -> DecompResultWith<MinKey, MajKey, SnzVal, PivotReport> where
 MajKey: PartialEq + Eq + Hash + Clone + Ord + Debug,
 MinKey: PartialEq + Eq + Hash + Clone + Ord + Debug,
-SnzVal: Add + Neg<Output=SnzVal> +Clone + PartialEq + InvMod<Output=SnzVal> + Mul<Output=SnzVal> + AddAssign + Debug,
+SnzVal: RingElement + Debug,
 Matrix: SmOracle<MajKey, MinKey, SnzVal>
 {
+    matrix.ring().check_field()?;
+
+    if matrix.ring().is_binary() {
+        let (rowoper, indexing) = decomp_row_gf2(matrix, maj_to_reduce);
+        return Ok((rowoper, indexing, PivotReport::default()));
+    }
+
     //initialize "rowoper" and "indexing"
     let length:f64 = maj_to_reduce.len() as f64;
     let capacity:usize = (length*1.2) as usize;
@@ -92,6 +200,8 @@ Matrix: SmOracle<MajKey, MinKey, SnzVal>
 
     let one: SnzVal = matrix.ring().identity_multiplicative.clone();
 
+    let mut report = PivotReport::default();
+
     //eliminate rows
 	while let Some(majkey) = maj_to_reduce.pop() {
 
@@ -135,14 +245,18 @@ Matrix: SmOracle<MajKey, MinKey, SnzVal>
                     row_reduced = multiply_hash_smoracle_version2(&row_rowoper, &indexing.index_2_majkey, matrix);
 
                     if let Some(dominator) = row_reduced.remove(&minkey) {
-                        if let Some(inverse) = matrix.ring().inverse(&dominator){
-                            let mut scale = -leading_entry*inverse;
-                            scale = matrix.ring().simplify(&scale);
-                            update_heap_hash(&matrix.ring(), &mut heap_reduced, &mut hash_reduced, &mut row_reduced, &scale);
-                            update_heap_hash(&matrix.ring(), &mut heap_rowoper, &mut hash_rowoper, &mut row_rowoper, &scale);
-                        }
+                        let inverse = matrix.ring().inverse(&dominator)
+                            .expect("the ring is a field, so every nonzero pivot is invertible");
+                        let mut scale = matrix.ring().multiply(&matrix.ring().negate(&leading_entry), &inverse);
+                        scale = matrix.ring().simplify(&scale);
+                        update_heap_hash(&matrix.ring(), &mut heap_reduced, &mut hash_reduced, &mut row_reduced, &scale);
+                        update_heap_hash(&matrix.ring(), &mut heap_rowoper, &mut hash_rowoper, &mut row_rowoper, &scale);
                     }
                 } else {
+                    if let Some(magnitude) = leading_entry.magnitude_in(&matrix.ring().ringspec) {
+                        if magnitude < pivot_threshold { report.num_small_pivots += 1; }
+                        report.smallest_pivot = Some(report.smallest_pivot.map_or(magnitude, |smallest| smallest.min(magnitude)));
+                    }
                     indexing.minkey_2_index.insert(minkey.clone(), rowoper.nummaj);
                     indexing.majkey_2_index.insert(majkey.clone(), rowoper.nummaj);
                     indexing.index_2_majkey.push(majkey);
@@ -166,5 +280,85 @@ Matrix: SmOracle<MajKey, MinKey, SnzVal>
 
     rowoper.shrink_to_fit();
     indexing.shrink_to_fit();
-    return (rowoper, indexing);
+    Ok((rowoper, indexing, report))
+}
+
+/// The body of [`decomp_row`](decomp_row) over Z/2Z
+///
+/// Every nonzero coefficient equals one, so we store each vector as the sorted list of keys where it is nonzero, and add
+/// two vectors by merging their lists and dropping the keys they share.  No coefficients are stored, multiplied or inverted,
+/// and there is no heap: the leading entry of a row is the first key of its list.
+fn decomp_row_gf2<MajKey, MinKey, SnzVal, Matrix>(
+    matrix:             &Matrix,
+    maj_to_reduce:      &mut Vec<MajKey>,
+) -> (CSM<usize, SnzVal>, Indexing<MinKey, MajKey>) where
+MajKey: PartialEq + Eq + Hash + Clone + Ord + Debug,
+MinKey: PartialEq + Eq + Hash + Clone + Ord + Debug,
+SnzVal: RingElement + Debug,
+Matrix: SmOracle<MajKey, MinKey, SnzVal>
+{
+    let capacity: usize = (maj_to_reduce.len() as f64 * 1.2) as usize;
+    // every row of `rowoper` is sorted, since its own index comes last and is the largest
+    let mut rowoper: CSM<usize, SnzVal> = CSM::with_capacity_sorted(capacity, MajorDimension::Row, matrix.ring().clone());
+    let mut indexing = Indexing::with_capacity(capacity);
+    let one: SnzVal = matrix.ring().identity_multiplicative.clone();
+
+    //eliminate rows
+    while let Some(majkey) = maj_to_reduce.pop() {
+
+        // This block is only desighed for Rips complex
+        if let Some((minkey, _)) = matrix.maj_itr(&majkey).next() {
+            if let Some(true) = matrix.is_pivot(&majkey, &minkey) {
+                indexing.minkey_2_index.insert(minkey.clone(), rowoper.nummaj);
+                indexing.majkey_2_index.insert(majkey.clone(), rowoper.nummaj);
+                indexing.index_2_majkey.push(majkey);
+                indexing.index_2_minkey.push(minkey);
+
+                rowoper.push_snzval(rowoper.nummaj, one.clone());
+                rowoper.majptr.push(rowoper.minind.len());
+                rowoper.nummaj += 1;
+                continue;
+            }
+        }
+
+        let mut support_reduced: Vec<MinKey> = matrix.maj_itr(&majkey)
+            .filter(|(_, val)| !matrix.ring().is_0(val))
+            .map(|(key, _)| key)
+            .collect();
+        support_reduced.sort();
+        let mut support_rowoper: Vec<usize> = Vec::new();
+
+        while let Some(minkey) = support_reduced.first().cloned() {
+            match indexing.minkey_2_index.get(&minkey) {
+                Some(index) => {
+                    let row_rowoper = &rowoper.minind[rowoper.majptr[*index]..rowoper.majptr[*index + 1]];
+                    let row_reduced = multiply_support_smoracle_gf2(row_rowoper, &indexing.index_2_majkey, matrix);
+                    support_reduced = symmetric_difference_sorted(std::mem::take(&mut support_reduced), row_reduced);
+                    support_rowoper = symmetric_difference_sorted(std::mem::take(&mut support_rowoper), row_rowoper.to_vec());
+                }
+                None => {
+                    indexing.minkey_2_index.insert(minkey.clone(), rowoper.nummaj);
+                    indexing.majkey_2_index.insert(majkey.clone(), rowoper.nummaj);
+                    indexing.index_2_majkey.push(majkey);
+                    indexing.index_2_minkey.push(minkey);
+
+                    for index in support_rowoper.iter() {
+                        rowoper.push_snzval(*index, one.clone());
+                    }
+                    rowoper.push_snzval(rowoper.nummaj, one.clone());
+                    rowoper.majptr.push(rowoper.minind.len());
+                    rowoper.nummaj += 1;
+                    break;
+                }
+            }
+        }
+    }
+
+    let mut ordered_minind: Vec<usize> = (0..indexing.index_2_minkey.len()).collect();
+    ordered_minind.sort_by(|a, b| indexing.index_2_minkey[*a].cmp(&indexing.index_2_minkey[*b]));
+    indexing.ordered_minind = ordered_minind;
+
+    rowoper.shrink_to_fit();
+    indexing.shrink_to_fit();
+    (rowoper, indexing)
 }
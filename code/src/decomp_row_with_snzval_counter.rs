@@ -18,9 +18,8 @@ use std::cmp::Reverse;
 use std::hash::Hash;
 use std::fmt::Debug;
 use std::collections::HashMap;
-use std::ops::{Add, Neg, AddAssign, Mul};
 
-use crate::matrix::{SmOracle, InvMod, MajorDimension, RingMetadata};
+use crate::matrix::{SmOracle, RingElement, Semiring, Ring, DivisionRing, MajorDimension, RingMetadata, DecompResultWith};
 use crate::csm::CSM;
 use crate::solver::multiply_hash_smoracle_version2;
 use crate::chx::Indexing;
@@ -40,12 +39,12 @@ pub fn update_heap_hash<MinKey, SnzVal>(
     row:            &mut HashMap<MinKey, SnzVal>,
     scale: &SnzVal) where
 MinKey: Eq + Hash + Ord + Clone,
-SnzVal: Clone + AddAssign + Mul<Output = SnzVal> + PartialEq + InvMod<Output = SnzVal>
+SnzVal: RingElement
 {
     for (key, val) in row.drain() {
-        let value = scale.clone()*val;
+        let value = ringmetadata.multiply(scale, &val);
         if let Some(x) = hash.get_mut(&key) {
-            *x += value;
+            *x = ringmetadata.add(x, &value);
             if ringmetadata.is_0(x) {
                 hash.remove(&key);
             }
@@ -65,15 +64,20 @@ SnzVal: Clone + AddAssign + Mul<Output = SnzVal> + PartialEq + InvMod<Output = S
 /// # Returns
 /// - A sparse matrix in compressed row format representing the row operation.
 /// - A Indexing recording pivot matrix, order of pivot major indices, order of pivot minor indices.
+///
+/// # Errors
+/// [`DecompError::NotAField`](crate::matrix::DecompError::NotAField) if the coefficient ring is not a field, and [`DecompError::UnsupportedRing`](crate::matrix::DecompError::UnsupportedRing) if the coefficient type cannot represent it (for example, `Modulus(n)` with `n` too large for the type); both are checked before any elimination is done.
 pub fn decomp_row_with_snzval_counter<MajKey, MinKey, SnzVal, Matrix>(
     matrix:             &Matrix,
     maj_to_reduce:      &mut Vec<MajKey>,
-) -> (CSM<usize, SnzVal>, Indexing<MinKey, MajKey>, usize) where
+) -> DecompResultWith<MinKey, MajKey, SnzVal, usize> where
 MajKey: PartialEq + Eq + Hash + Clone + Ord + Debug,
 MinKey: PartialEq + Eq + Hash + Clone + Ord + Debug,
-SnzVal: Add + Neg<Output=SnzVal> +Clone + PartialEq + InvMod<Output=SnzVal> + Mul<Output=SnzVal> + AddAssign + Debug,
+SnzVal: RingElement + Debug,
 Matrix: SmOracle<MajKey, MinKey, SnzVal>
 {
+    matrix.ring().check_field()?;
+
     //initialize "rowoper" and "indexing"
     let length:f64 = maj_to_reduce.len() as f64;
     let capacity:usize = (length*1.2) as usize;
@@ -138,13 +142,13 @@ Matrix: SmOracle<MajKey, MinKey, SnzVal>
                     row_reduced = multiply_hash_smoracle_version2(&row_rowoper, &indexing.index_2_majkey, matrix);
 
                     if let Some(dominator) = row_reduced.remove(&minkey) {
-                        if let Some(inverse) = matrix.ring().inverse(&dominator){
-                            let mut scale = -leading_entry*inverse;
-                            scale = matrix.ring().simplify(&scale);
+                        let inverse = matrix.ring().inverse(&dominator)
+                            .expect("the ring is a field, so every nonzero pivot is invertible");
+                        let mut scale = matrix.ring().multiply(&matrix.ring().negate(&leading_entry), &inverse);
+                        scale = matrix.ring().simplify(&scale);
 
-                            update_heap_hash(&matrix.ring(), &mut heap_reduced, &mut hash_reduced, &mut row_reduced, &scale);
-                            update_heap_hash(&matrix.ring(), &mut heap_rowoper, &mut hash_rowoper, &mut row_rowoper, &scale);
-                        }
+                        update_heap_hash(&matrix.ring(), &mut heap_reduced, &mut hash_reduced, &mut row_reduced, &scale);
+                        update_heap_hash(&matrix.ring(), &mut heap_rowoper, &mut hash_rowoper, &mut row_rowoper, &scale);
                     }
                 } else {
                     indexing.minkey_2_index.insert(minkey.clone(), rowoper.nummaj);
@@ -172,5 +176,5 @@ Matrix: SmOracle<MajKey, MinKey, SnzVal>
 
     rowoper.shrink_to_fit();
     indexing.shrink_to_fit();
-    return (rowoper, indexing, counter);
+    Ok((rowoper, indexing, counter))
 }
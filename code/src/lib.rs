@@ -16,6 +16,8 @@
 //!
 //! * reference information for [sparse matrix oracles](matrix)
 //! * reference information for [chain complex oracles](chx)
+//! * reference information for [U-match factorization](umatch)
+//! * reference information for [persistent homology with torsion over the integers](decomp_pid)
 //!
 
 //! # Sparse matrix oracles
@@ -188,16 +190,17 @@
 //!     //  - the boundary matrix is "row-major" so we call rows "major fields"
 //!     // ----------------------------------------------------------------------------------
 //!     // create an iterator that runs over the structural nonzero entries of the row
-//!     // each item returned by the iterator is a tuple of form (weighted_simplex, coefficient)
+//!     // each item returned by the iterator is a tuple of form (weighted_simplex, coefficient);
+//!     // coefficients are reduced modulo 3, so the coefficient -1 appears as 2
 //!     let major_field = D.maj_itr( &simplex_d0 );
 //!
 //!
 //!     // the following for-loop should print the following:
 //!     // ```text
 //!     // Structural nonzero entries of a row corresponding to a 0-simplex:
-//!     // (Simplex { filvalue: 1, vertices: [0, 1] }, -1)
-//!     // (Simplex { filvalue: 2, vertices: [0, 2] }, -1)
-//!     // (Simplex { filvalue: 1, vertices: [0, 3] }, -1)
+//!     // (Simplex { filvalue: 1, vertices: [0, 1] }, 2)
+//!     // (Simplex { filvalue: 2, vertices: [0, 2] }, 2)
+//!     // (Simplex { filvalue: 1, vertices: [0, 3] }, 2)
 //!     // ```
 //!     println!("Structural nonzero entries of a row corresponding to a 0-simplex:");
 //!     for item in major_field  {
@@ -206,9 +209,9 @@
 //!
 //!     // check to ensure that the output is correct:
 //!     let mut correct_val : Vec< (Simplex<i64>, i16) >  =
-//!                       vec![ (Simplex{ filvalue: 1, vertices: vec![0, 1] }, -1),
-//!                             (Simplex{ filvalue: 2, vertices: vec![0, 2] }, -1),
-//!                             (Simplex{ filvalue: 1, vertices: vec![0, 3] }, -1) ];
+//!                       vec![ (Simplex{ filvalue: 1, vertices: vec![0, 1] }, 2),
+//!                             (Simplex{ filvalue: 2, vertices: vec![0, 2] }, 2),
+//!                             (Simplex{ filvalue: 1, vertices: vec![0, 3] }, 2) ];
 //!
 //!     let major_field2 = D.maj_itr( &simplex_d0 ); // this re-creates the iterator
 //!     std::assert_eq!( major_field2.eq(correct_val.iter().map( |x| x.clone() ) ), true);
@@ -226,7 +229,7 @@
 //!     // ```text
 //!     // Structural nonzero entries of a column corresponding to a 1-simplex:
 //!     // (Simplex { filvalue: 0, vertices: [1] }, 1)
-//!     // (Simplex { filvalue: 0, vertices: [0] }, -1)
+//!     // (Simplex { filvalue: 0, vertices: [0] }, 2)
 //!     // ```
 //!     println!("Structural nonzero entries of a column corresponding to a 1-simplex:");
 //!     for item in minor_field  {
@@ -236,7 +239,7 @@
 //!     // check to ensure that the output is correct:
 //!     let mut correct_val : Vec< (Simplex<i64>, i16) >  =
 //!                       vec![  (Simplex{ filvalue: 0, vertices: vec![1] },  1),
-//!                              (Simplex{ filvalue: 0, vertices: vec![0] }, -1)  ];
+//!                              (Simplex{ filvalue: 0, vertices: vec![0] }, 2)  ];
 //!
 //!     let minor_field2 = D.min_itr( &simplex_d1 ); // this re-creates the iterator
 //!     std::assert_eq!( minor_field2.eq(correct_val.iter().map( |x| x.clone() ) ), true);
@@ -245,14 +248,14 @@
 //!     // ----------------------------------------------------------------------------------
 //!     // Factor the complex
 //!     // ----------------------------------------------------------------------------------
-//!     let factored_complex = exhact::chx::factor_chain_complex(&chx, dim+1);
+//!     let factored_complex = exhact::chx::factor_chain_complex(&chx, dim+1).unwrap();
 //!
 //!
 //!     // ----------------------------------------------------------------------------------
 //!     // Read barcodes / check for correctness
 //!     // ----------------------------------------------------------------------------------
 //!     // predefine the set of correct solutions
-//!     let correct_barcodes = vec![    vec![ (0, 2), (0, 1), (0, 1), (0, 1) ], // dimension 0
+//!     let correct_barcodes = vec![    vec![ (0, 0), (0, 1), (0, 1), (0, 1) ], // dimension 0; an infinite bar born at b is written (b, b)
 //!                                     vec![ (1, 2) ],                         // dimension 1
 //!                                     vec![],                                 // dimension 2
 //!                                     vec![]                                  // dimension 3
@@ -306,7 +309,16 @@
 //!     // Get the birth and death filtraitons of a chain
 //!     //    - here "chain" is formalized as a hashmap mapping keys to coefficients
 //!     // ----------------------------------------------------------------------------------
-//!     // this part is not implemented yet.
+//!     // the square 0-1-2-3 is a cycle that appears at filtration 1 and fills in at 2, when the diagonals appear
+//!     let mut chain = std::collections::HashMap::new();
+//!     chain.insert( Simplex{ filvalue: 1, vertices: vec![0, 1] }, 1 );
+//!     chain.insert( Simplex{ filvalue: 1, vertices: vec![1, 2] }, 1 );
+//!     chain.insert( Simplex{ filvalue: 1, vertices: vec![2, 3] }, 1 );
+//!     chain.insert( Simplex{ filvalue: 1, vertices: vec![0, 3] }, -1 );
+//!
+//!     let birth_death = factored_complex.chain_birth_death(1, &chain);
+//!     println!("Birth and death of the chain: {:?}", birth_death);
+//!     std::assert_eq!( birth_death, Some( (1, Some(2)) ) );
 //! }
 //! ```
 
@@ -315,10 +327,12 @@
 
 
 pub mod csm;
+pub mod mtx;
 pub mod matrix;
 
 pub mod chx;
 pub mod solver;
+pub mod umatch;
 
 pub mod clique;
 pub mod cubical;
@@ -328,3 +342,4 @@ pub mod decomp_col;
 pub mod decomp_row_use_pairs;
 pub mod decomp_col_use_pairs;
 pub mod decomp_row_with_snzval_counter;
+pub mod decomp_pid;
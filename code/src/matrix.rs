@@ -78,12 +78,17 @@ A *sparse matrix oracle* is an object that allows users to access rows, columns,
 // The following sketch API is an attempt to deal with some of these issues.
 
 use core::ops::Range;
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::hash::Hash;
 use std::fmt::Debug;
+use std::marker::PhantomData;
 use crate::csm::CSM;
+use crate::chx::Indexing;
 use num::rational::Ratio;
+use num::{BigInt, Integer, One, Signed, Zero};
 
 
 struct BasisID{}
@@ -103,12 +108,13 @@ struct BasisID{}
 // -----------------------
 
 /// Represents the "name" of a given rings.  Unlike a [`RingMetadata`](RingMetadata) object, it does not contain sufficient information for Rust to infer how to execute addition, subtraction, multiplication, and (sometimes) division.
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum RingSpec{
     Integer,
     Modulus(usize),
     Rational,
-    Float
+    /// Real numbers, represented by floating point values; the parameter is a tolerance `epsilon`, and values with absolute value at most `epsilon` are treated as zero.
+    Float(f64)
 }
 
 /// Stores the data needed for rust to perform basic ring operations:
@@ -135,120 +141,721 @@ type IntegerType = i16;
 /// - an interger named 'inverse' such that '(inverse*integer)%prime = gcd(prime,integer)'
 /// the greatest common divisor gcd(prime,integer)
 pub fn euclidean(prime: IntegerType, integer:IntegerType) -> (IntegerType,IntegerType) {
-    let mut integer = integer%prime;
-    if integer < 0 { integer += prime; }
-    let mut x;
+    let (inverse, gcd) = euclidean_i128(prime as i128, integer as i128);
+    return (inverse as IntegerType, gcd as IntegerType);
+}
+
+/// The Euclidean algorithm, carried out with 128-bit integers so that intermediate values never overflow.  See [`euclidean`](euclidean).
+///
+/// The Bezout coefficient is kept in `[0, prime)` and updated with unsigned 128-bit products, so this is safe for any modulus that fits in a `u64`.
+fn euclidean_i128(prime: i128, integer: i128) -> (i128, i128) {
+    let modulus = prime as u128;
     let mut y0 = prime;
-    let mut y1 = integer;
-    let mut a0 = 0;
-    let mut a1 = 1;
+    let mut y1 = integer.rem_euclid(prime);
+    let mut a0: u128 = 0;
+    let mut a1: u128 = 1;
     while y1>0 && y0%y1 != 0 {
-        x = y0/y1;
+        let x = (y0/y1) as u128;
         let temp = y0;
         y0 = y1;
         y1 = temp%y0;
         let temp = a0;
         a0 = a1;
-        a1 = (temp - x*a0)%prime;
+        a1 = (temp + modulus - (x % modulus) * a0 % modulus) % modulus;
     }
-    if a1 < 0 {
-        a1 += prime;
-    }
-    return (a1,y1);
+    (a1 as i128, y1)
 }
 
-/// A trait which makes sure we can inverse coefficients of different types. Even though each type has their own inverse method, to work on them uniformly, we need to define this trait to unify their inverse operations
-pub trait InvMod {
-    type Output; // the output type
+/// Panic with an explanation that a coefficient type does not support a given ring.
+fn unsupported_ring(ringspec: &RingSpec, typename: &str) -> ! {
+    let ringname = match ringspec {
+        RingSpec::Integer => "Integer".to_string(),
+        RingSpec::Modulus(modulus) => format!("Modulus({})", modulus),
+        RingSpec::Rational => "Rational".to_string(),
+        RingSpec::Float(epsilon) => format!("Float({})", epsilon),
+    };
+    panic!("coefficients of type {} cannot represent elements of the ring RingSpec::{}", typename, ringname);
+}
 
-    /// Find the inverse module given (prime) interger
-    ///
-    /// # Parameters
-    /// -`module`: given (prime) interger
-    ///
-    /// # Returns
-    /// If inverse exists return Some(inverse); otherwise return None
-    fn inverse_mod(&self, module: usize) -> Option<Self::Output>;
+/// A trait for types that can be used as coefficients (structural nonzero values) of a sparse matrix.
+///
+/// The same type can represent elements of several different rings (for example, an `i16` can represent an integer or an element of a prime field), so each operation takes the [`RingSpec`](RingSpec) of the ring as an argument.  Implementations should panic if the type cannot represent elements of the given ring.  Most users won't call these methods directly; instead, they'll call the methods of the [`Semiring`](Semiring), [`Ring`](Ring) and [`DivisionRing`](DivisionRing) traits on a [`RingMetadata`](RingMetadata) object.
+pub trait RingElement: Clone + PartialEq {
 
-    /// module by given interger
-    ///
-    /// # Parameters
-    /// -`module`: given (prime) interger
-    fn module_by(&self, module: usize) -> Self::Output;
+    /// The sum `self + other`
+    fn add_in(&self, other: &Self, ringspec: &RingSpec) -> Self;
+
+    /// The product `self * other`
+    fn multiply_in(&self, other: &Self, ringspec: &RingSpec) -> Self;
+
+    /// The additive inverse `-self`
+    fn negate_in(&self, ringspec: &RingSpec) -> Self;
+
+    /// True if `self` represents zero
+    fn is_zero_in(&self, ringspec: &RingSpec) -> bool;
+
+    /// The multiplicative inverse of `self`, or `None` if `self` is not a unit
+    fn invert_in(&self, ringspec: &RingSpec) -> Option<Self>;
+
+    /// A canonical representative of `self`; for example, 6 in Modulus(5) is simplified to 1.
+    fn simplify_in(&self, _ringspec: &RingSpec) -> Self { self.clone() }
+
+    /// The absolute value of `self`, for coefficient types where this is meaningful (e.g. floating point numbers), and `None` otherwise.  Used to detect numerically unstable pivots.
+    fn magnitude_in(&self, _ringspec: &RingSpec) -> Option<f64> { None }
+
+    /// True if values of this type can represent every element of the ring; for example, a `u32` cannot represent Z/nZ when `n - 1` does not fit in 32 bits.  The elimination routines check this before they start (see [`RingMetadata::check_field`](RingMetadata::check_field)).
+    fn represents(_ringspec: &RingSpec) -> bool where Self: Sized { true }
+}
+
+/// A trait for coefficient types that support division with remainder, as in a Euclidean domain (for example, the integers).
+///
+/// This is what the principal ideal domain routines in [`decomp_pid`](crate::decomp_pid) need in order to reduce a matrix without ever dividing by a non-unit.
+pub trait EuclideanElement: RingElement {
+
+    /// A pair `(q, r)` such that `self = q * other + r`, where `r` is "smaller" than `other`; for the integers, `0 <= r < |other|`.
+    fn divide_with_remainder_in(&self, other: &Self, ringspec: &RingSpec) -> (Self, Self);
+
+    /// A unit `u` such that `u * self` is the preferred representative of the associates of `self`; for the integers this is the sign of `self`.
+    fn normal_unit_in(&self, ringspec: &RingSpec) -> Self;
 }
 
-/// Implimentation of trait InvMod for rational type
-impl InvMod for Ratio<IntegerType>{
-    type Output = Ratio<IntegerType>;
-    fn inverse_mod(&self, _prime: usize) -> Option<Self::Output>{
-        if *self.numer() == 0 || *self.denom() == 0 { return None; }
-        else {
-            return Some( Ratio::new(*self.denom(), *self.numer()) );
+/// Implimentation of trait RingElement for interger types
+///
+/// Values can represent integers (`RingSpec::Integer`) or elements of Z/nZ (`RingSpec::Modulus(n)`).  Modular arithmetic is carried out with 128-bit intermediate values, so it never overflows; however, the modulus must fit in the type.  Sums, products and negations modulo n are reduced to the range `[0, n)`, as for unsigned types.  Integer arithmetic is checked, and panics on overflow; use `Ratio<BigInt>` if the entries may grow without bound.
+macro_rules! impl_ring_element_for_signed_integer {
+    ($integer:ty) => {
+        impl RingElement for $integer {
+            fn represents(ringspec: &RingSpec) -> bool {
+                match ringspec {
+                    RingSpec::Integer => true,
+                    RingSpec::Modulus(modulus) => *modulus >= 1 && (*modulus - 1) as u128 <= <$integer>::MAX as u128,
+                    _ => false
+                }
+            }
+
+            fn add_in(&self, other: &Self, ringspec: &RingSpec) -> Self {
+                match ringspec {
+                    RingSpec::Integer => self.checked_add(*other)
+                        .unwrap_or_else(|| panic!("integer overflow: {} + {} does not fit in {}", self, other, stringify!($integer))),
+                    RingSpec::Modulus(modulus) => ((*self as i128 + *other as i128).rem_euclid(*modulus as i128)) as $integer,
+                    _ => unsupported_ring(ringspec, stringify!($integer))
+                }
+            }
+
+            fn multiply_in(&self, other: &Self, ringspec: &RingSpec) -> Self {
+                match ringspec {
+                    RingSpec::Integer => self.checked_mul(*other)
+                        .unwrap_or_else(|| panic!("integer overflow: {} * {} does not fit in {}", self, other, stringify!($integer))),
+                    RingSpec::Modulus(modulus) => ((*self as i128 * *other as i128).rem_euclid(*modulus as i128)) as $integer,
+                    _ => unsupported_ring(ringspec, stringify!($integer))
+                }
+            }
+
+            fn negate_in(&self, ringspec: &RingSpec) -> Self {
+                match ringspec {
+                    RingSpec::Integer => self.checked_neg()
+                        .unwrap_or_else(|| panic!("integer overflow: -({}) does not fit in {}", self, stringify!($integer))),
+                    RingSpec::Modulus(modulus) => (-(*self as i128)).rem_euclid(*modulus as i128) as $integer,
+                    _ => unsupported_ring(ringspec, stringify!($integer))
+                }
+            }
+
+            fn is_zero_in(&self, ringspec: &RingSpec) -> bool {
+                match ringspec {
+                    RingSpec::Integer => *self == 0,
+                    RingSpec::Modulus(modulus) => (*self as i128).rem_euclid(*modulus as i128) == 0,
+                    _ => unsupported_ring(ringspec, stringify!($integer))
+                }
+            }
+
+            fn invert_in(&self, ringspec: &RingSpec) -> Option<Self> {
+                match ringspec {
+                    RingSpec::Integer => if *self == 1 || *self == -1 { Some(*self) } else { None },
+                    RingSpec::Modulus(modulus) => {
+                        let (inverse, gcd) = euclidean_i128(*modulus as i128, *self as i128);
+                        if gcd != 1 { None } else { Some(inverse as $integer) }
+                    }
+                    _ => unsupported_ring(ringspec, stringify!($integer))
+                }
+            }
+
+            fn simplify_in(&self, ringspec: &RingSpec) -> Self {
+                match ringspec {
+                    RingSpec::Integer => *self,
+                    RingSpec::Modulus(modulus) => (*self as i128).rem_euclid(*modulus as i128) as $integer,
+                    _ => unsupported_ring(ringspec, stringify!($integer))
+                }
+            }
         }
-    }
 
-    fn module_by(&self, prime: usize) -> Self::Output {
-        return self.clone();
-    }
+        impl EuclideanElement for $integer {
+            fn divide_with_remainder_in(&self, other: &Self, ringspec: &RingSpec) -> (Self, Self) {
+                match ringspec {
+                    RingSpec::Integer => (self.div_euclid(*other), self.rem_euclid(*other)),
+                    RingSpec::Modulus(_) => {
+                        let inverse = other.invert_in(ringspec)
+                            .expect("division by an element that is not invertible in Z/nZ");
+                        (self.multiply_in(&inverse, ringspec), 0)
+                    }
+                    _ => unsupported_ring(ringspec, stringify!($integer))
+                }
+            }
+
+            fn normal_unit_in(&self, ringspec: &RingSpec) -> Self {
+                match ringspec {
+                    RingSpec::Integer => if *self < 0 { -1 } else { 1 },
+                    RingSpec::Modulus(_) => self.invert_in(ringspec).unwrap_or(1),
+                    _ => unsupported_ring(ringspec, stringify!($integer))
+                }
+            }
+        }
+    };
 }
 
-/// Implimentation of trait InvMod for interger type
-impl InvMod for IntegerType{
-    type Output = IntegerType;
+impl_ring_element_for_signed_integer!(i16);
+impl_ring_element_for_signed_integer!(i32);
+impl_ring_element_for_signed_integer!(i64);
 
-    fn inverse_mod(&self, prime: usize) -> Option<Self::Output>{
-        if euclidean(prime as IntegerType, *self).1 != 1 { return None; }
-        else { return Some(euclidean(prime as IntegerType, *self).0); }
-    }
+/// Implimentation of trait RingElement for unsigned interger types
+///
+/// Values represent elements of Z/nZ (`RingSpec::Modulus(n)`) only; every value is kept in the range `[0, n)`.  Products are widened to 128 bits before they are reduced, so any modulus that fits in the type is supported (for example 65521 or 2^31-1 with `u32`, and primes up to 2^64 with `u64`).  The elimination routines return [`DecompError::UnsupportedRing`](DecompError::UnsupportedRing) for a modulus that does not fit.
+///
+/// ```
+/// use exhact::matrix::{RingSpec, RingMetadata, MajorDimension, Semiring, DivisionRing, DecompError};
+/// use exhact::csm::CSM;
+/// use exhact::decomp_row::decomp_row;
+///
+/// let prime = 2147483647; // 2^31 - 1
+/// let ringmetadata = RingMetadata{ ringspec: RingSpec::Modulus(prime), identity_additive: 0u32, identity_multiplicative: 1u32 };
+///
+/// // inverses round-trip, even though the products involved do not fit in 32 bits
+/// for value in [2u32, 65521, 2147483646] {
+///     let inverse = ringmetadata.inverse(&value).unwrap();
+///     assert_eq!(ringmetadata.multiply(&value, &inverse), 1);
+/// }
+///
+/// // the 2x2 matrix [[2, 3], [4, 6 + 2^31 - 1]] has rank 1 modulo 2^31 - 1
+/// let mut matrix = CSM::new(MajorDimension::Row, ringmetadata);
+/// matrix.append_maj(&mut vec![(0, 2), (1, 3)].into_iter().collect());
+/// matrix.append_maj(&mut vec![(0, 4), (1, 6)].into_iter().collect());
+/// let (_, indexing) = decomp_row(&matrix, &mut vec![0, 1]).unwrap();
+/// assert_eq!(indexing.index_2_majkey, vec![1]);
+///
+/// // a modulus that does not fit in 32 bits is rejected before elimination starts
+/// let toolarge = RingMetadata{ ringspec: RingSpec::Modulus(1 << 40), identity_additive: 0u32, identity_multiplicative: 1u32 };
+/// let matrix: CSM<usize, u32> = CSM::new(MajorDimension::Row, toolarge);
+/// assert_eq!(decomp_row(&matrix, &mut vec![]).err(), Some(DecompError::UnsupportedRing(RingSpec::Modulus(1 << 40))));
+/// ```
+macro_rules! impl_ring_element_for_unsigned_integer {
+    ($integer:ty) => {
+        impl RingElement for $integer {
+            fn represents(ringspec: &RingSpec) -> bool {
+                match ringspec {
+                    RingSpec::Modulus(modulus) => *modulus >= 1 && (*modulus - 1) as u128 <= <$integer>::MAX as u128,
+                    _ => false
+                }
+            }
 
-    fn module_by(&self, prime: usize) -> Self::Output {
-        let mut simple_form = *self%(prime as IntegerType);
-        if simple_form < 0 { simple_form = simple_form + prime as IntegerType; }
-        return simple_form;
-    }
+            fn add_in(&self, other: &Self, ringspec: &RingSpec) -> Self {
+                match ringspec {
+                    RingSpec::Modulus(modulus) => ((*self as u128 + *other as u128) % *modulus as u128) as $integer,
+                    _ => unsupported_ring(ringspec, stringify!($integer))
+                }
+            }
+
+            fn multiply_in(&self, other: &Self, ringspec: &RingSpec) -> Self {
+                match ringspec {
+                    RingSpec::Modulus(modulus) => ((*self as u128 * *other as u128) % *modulus as u128) as $integer,
+                    _ => unsupported_ring(ringspec, stringify!($integer))
+                }
+            }
+
+            fn negate_in(&self, ringspec: &RingSpec) -> Self {
+                match ringspec {
+                    RingSpec::Modulus(modulus) => {
+                        let modulus = *modulus as u128;
+                        ((modulus - *self as u128 % modulus) % modulus) as $integer
+                    }
+                    _ => unsupported_ring(ringspec, stringify!($integer))
+                }
+            }
+
+            fn is_zero_in(&self, ringspec: &RingSpec) -> bool {
+                match ringspec {
+                    RingSpec::Modulus(modulus) => *self as u128 % *modulus as u128 == 0,
+                    _ => unsupported_ring(ringspec, stringify!($integer))
+                }
+            }
+
+            fn invert_in(&self, ringspec: &RingSpec) -> Option<Self> {
+                match ringspec {
+                    RingSpec::Modulus(modulus) => {
+                        let (inverse, gcd) = euclidean_i128(*modulus as i128, *self as i128);
+                        if gcd != 1 { None } else { Some(inverse as $integer) }
+                    }
+                    _ => unsupported_ring(ringspec, stringify!($integer))
+                }
+            }
+
+            fn simplify_in(&self, ringspec: &RingSpec) -> Self {
+                match ringspec {
+                    RingSpec::Modulus(modulus) => (*self as u128 % *modulus as u128) as $integer,
+                    _ => unsupported_ring(ringspec, stringify!($integer))
+                }
+            }
+        }
+    };
 }
 
-/// Methods of RingMetadata struct
-impl<ElementType: Clone + PartialEq + InvMod<Output=ElementType>> RingMetadata<ElementType> {
+impl_ring_element_for_unsigned_integer!(u32);
+impl_ring_element_for_unsigned_integer!(u64);
 
-    /// Find the inverse of x and returns None if does not exist
-    pub fn inverse( &self, x: &ElementType ) -> Option<ElementType> {
-        match self.ringspec {
-            RingSpec::Integer => {
-                return Some(x.clone());
+/// Implimentation of trait RingElement for rational types
+///
+/// Values represent rational numbers (`RingSpec::Rational`) only.  `Ratio<i16>` is compact but overflows quickly during elimination; `Ratio<BigInt>` (alias `BigRational`) is exact for inputs of any size.
+///
+/// ```
+/// use exhact::matrix::{RingSpec, RingMetadata, MajorDimension, Semiring, DivisionRing};
+/// use exhact::clique::CliqueComplex;
+/// use exhact::chx::factor_chain_complex;
+/// use num::{BigInt, BigRational};
+///
+/// let rational = |numer: i64, denom: i64| BigRational::new(BigInt::from(numer), BigInt::from(denom));
+/// let ringmetadata = RingMetadata{
+///     ringspec: RingSpec::Rational,
+///     identity_additive: rational(0, 1),
+///     identity_multiplicative: rational(1, 1),
+/// };
+///
+/// // exact arithmetic, with no overflow even when numerators and denominators are large
+/// assert_eq!(ringmetadata.add(&rational(1, 3), &rational(1, 6)), rational(1, 2));
+/// assert_eq!(ringmetadata.inverse(&rational(-2, 3)), Some(rational(-3, 2)));
+/// let big = rational(i64::MAX, 3);
+/// let square = ringmetadata.multiply(&big, &big);
+/// assert_eq!(ringmetadata.multiply(&square, &ringmetadata.inverse(&big).unwrap()), big);
+///
+/// // 6 points evenly spaced around a circle; the distance between two points is the number of steps between them
+/// let dismat: Vec<Vec<i64>> = (0..6i64).map(|i| {
+///     (0..6i64).map(|j| std::cmp::min((i - j).abs(), 6 - (i - j).abs())).collect()
+/// }).collect();
+/// fn complex<SnzVal: Clone>( dismat: &Vec<Vec<i64>>, ringmetadata: RingMetadata<SnzVal> ) -> CliqueComplex<SnzVal, i64> {
+///     CliqueComplex {
+///         dissimilarity_matrix: dismat.clone(),
+///         dissimilarity_value_max: 3,
+///         safe_homology_degrees_to_build_boundaries: vec![1, 2],
+///         major_dimension: MajorDimension::Row,
+///         ringmetadata,
+///         simplex_count: Vec::new()
+///     }
+/// }
+///
+/// // the rational barcodes agree with those over a large prime field
+/// let chx_rational = complex(&dismat, ringmetadata);
+/// let factored_rational = factor_chain_complex(&chx_rational, 2).unwrap();
+/// let chx_modp = complex(&dismat, RingMetadata{ ringspec: RingSpec::Modulus(2147483647), identity_additive: 0i64, identity_multiplicative: 1i64 });
+/// let factored_modp = factor_chain_complex(&chx_modp, 2).unwrap();
+/// for degree in 0..2 {
+///     let mut barcode_rational = factored_rational.barcode(degree);
+///     let mut barcode_modp = factored_modp.barcode(degree);
+///     barcode_rational.sort();
+///     barcode_modp.sort();
+///     assert_eq!(barcode_rational, barcode_modp);
+/// }
+///
+/// // the circle is born when the edges appear, and filled in when the triangles appear
+/// assert_eq!(factored_rational.barcode(1), vec![(1, 2)]);
+/// ```
+macro_rules! impl_ring_element_for_ratio {
+    ($ratio:ty) => {
+        impl RingElement for $ratio {
+            fn represents(ringspec: &RingSpec) -> bool { matches!(ringspec, RingSpec::Rational) }
+
+            fn add_in(&self, other: &Self, ringspec: &RingSpec) -> Self {
+                match ringspec {
+                    RingSpec::Rational => self + other,
+                    _ => unsupported_ring(ringspec, stringify!($ratio))
+                }
+            }
+
+            fn multiply_in(&self, other: &Self, ringspec: &RingSpec) -> Self {
+                match ringspec {
+                    RingSpec::Rational => self * other,
+                    _ => unsupported_ring(ringspec, stringify!($ratio))
+                }
             }
-            RingSpec::Modulus(prime) => {
-                x.inverse_mod(prime)
+
+            fn negate_in(&self, ringspec: &RingSpec) -> Self {
+                match ringspec {
+                    RingSpec::Rational => -self,
+                    _ => unsupported_ring(ringspec, stringify!($ratio))
+                }
             }
-            RingSpec::Rational => {
-                x.inverse_mod(0)
+
+            fn is_zero_in(&self, ringspec: &RingSpec) -> bool {
+                match ringspec {
+                    RingSpec::Rational => self.numer().is_zero(),
+                    _ => unsupported_ring(ringspec, stringify!($ratio))
+                }
             }
-            RingSpec::Float => { return Some(x.clone()); }
+
+            fn invert_in(&self, ringspec: &RingSpec) -> Option<Self> {
+                match ringspec {
+                    RingSpec::Rational => if self.numer().is_zero() { None } else { Some(self.recip()) },
+                    _ => unsupported_ring(ringspec, stringify!($ratio))
+                }
+            }
+        }
+    };
+}
+
+impl_ring_element_for_ratio!(Ratio<i16>);
+impl_ring_element_for_ratio!(Ratio<BigInt>);
+
+/// Implimentation of trait RingElement for arbitrary precision integers
+///
+/// Values represent integers (`RingSpec::Integer`) only; unlike the fixed width integer types, these never overflow.
+impl RingElement for BigInt {
+    fn represents(ringspec: &RingSpec) -> bool { matches!(ringspec, RingSpec::Integer) }
+
+    fn add_in(&self, other: &Self, ringspec: &RingSpec) -> Self {
+        match ringspec {
+            RingSpec::Integer => self + other,
+            _ => unsupported_ring(ringspec, "BigInt")
         }
     }
 
-    /// Simplify the representation of x. For example, 6 in Modulus(5) is simplified to 1; 6/3 in rational is simplified to 2/1.
-    pub fn simplify(&self, x: &ElementType) -> ElementType {
-        match self.ringspec {
+    fn multiply_in(&self, other: &Self, ringspec: &RingSpec) -> Self {
+        match ringspec {
+            RingSpec::Integer => self * other,
+            _ => unsupported_ring(ringspec, "BigInt")
+        }
+    }
+
+    fn negate_in(&self, ringspec: &RingSpec) -> Self {
+        match ringspec {
+            RingSpec::Integer => -self,
+            _ => unsupported_ring(ringspec, "BigInt")
+        }
+    }
+
+    fn is_zero_in(&self, ringspec: &RingSpec) -> bool {
+        match ringspec {
+            RingSpec::Integer => self.is_zero(),
+            _ => unsupported_ring(ringspec, "BigInt")
+        }
+    }
+
+    fn invert_in(&self, ringspec: &RingSpec) -> Option<Self> {
+        match ringspec {
+            RingSpec::Integer => if self.abs().is_one() { Some(self.clone()) } else { None },
+            _ => unsupported_ring(ringspec, "BigInt")
+        }
+    }
+}
+
+impl EuclideanElement for BigInt {
+    fn divide_with_remainder_in(&self, other: &Self, ringspec: &RingSpec) -> (Self, Self) {
+        match ringspec {
             RingSpec::Integer => {
-                return x.clone();
-            }
-            RingSpec::Modulus(prime) => {
-                x.module_by(prime)
+                // floor division, adjusted so that the remainder lies in [0, |other|)
+                let (mut quotient, mut remainder) = self.div_mod_floor(other);
+                if remainder.is_negative() {
+                    quotient += 1;
+                    remainder -= other;
+                }
+                (quotient, remainder)
             }
-            RingSpec::Rational => { return x.clone(); }
-            RingSpec::Float => { return x.clone(); }
+            _ => unsupported_ring(ringspec, "BigInt")
+        }
+    }
+
+    fn normal_unit_in(&self, ringspec: &RingSpec) -> Self {
+        match ringspec {
+            RingSpec::Integer => if self.is_negative() { BigInt::from(-1) } else { BigInt::from(1) },
+            _ => unsupported_ring(ringspec, "BigInt")
+        }
+    }
+}
+
+/// Implimentation of trait RingElement for floating point type
+///
+/// Values represent real numbers (`RingSpec::Float(epsilon)`).  Any value with absolute value at most `epsilon` is treated as zero, and is simplified to exactly `0.0`.
+impl RingElement for f64 {
+    fn represents(ringspec: &RingSpec) -> bool { matches!(ringspec, RingSpec::Float(_)) }
+
+    fn add_in(&self, other: &Self, ringspec: &RingSpec) -> Self {
+        match ringspec {
+            RingSpec::Float(_) => self + other,
+            _ => unsupported_ring(ringspec, "f64")
+        }
+    }
+
+    fn multiply_in(&self, other: &Self, ringspec: &RingSpec) -> Self {
+        match ringspec {
+            RingSpec::Float(_) => self * other,
+            _ => unsupported_ring(ringspec, "f64")
+        }
+    }
+
+    fn negate_in(&self, ringspec: &RingSpec) -> Self {
+        match ringspec {
+            RingSpec::Float(_) => -self,
+            _ => unsupported_ring(ringspec, "f64")
+        }
+    }
+
+    fn is_zero_in(&self, ringspec: &RingSpec) -> bool {
+        match ringspec {
+            RingSpec::Float(epsilon) => self.abs() <= *epsilon,
+            _ => unsupported_ring(ringspec, "f64")
+        }
+    }
+
+    fn invert_in(&self, ringspec: &RingSpec) -> Option<Self> {
+        match ringspec {
+            RingSpec::Float(epsilon) => if self.abs() <= *epsilon { None } else { Some(1.0 / self) },
+            _ => unsupported_ring(ringspec, "f64")
+        }
+    }
+
+    fn simplify_in(&self, ringspec: &RingSpec) -> Self {
+        match ringspec {
+            RingSpec::Float(epsilon) => if self.abs() <= *epsilon { 0.0 } else { *self },
+            _ => unsupported_ring(ringspec, "f64")
+        }
+    }
+
+    fn magnitude_in(&self, _ringspec: &RingSpec) -> Option<f64> { Some(self.abs()) }
+}
+
+/// An element of the two element field Z/2Z, stored as a `bool`.
+///
+/// A `Z2Bool` occupies a single byte (half the space of an `i16`), addition is exclusive or, and the only nonzero element is its own inverse.  Use it with `RingSpec::Modulus(2)`, for example
+/// `RingMetadata{ ringspec: RingSpec::Modulus(2), identity_additive: Z2Bool(false), identity_multiplicative: Z2Bool(true) }`.
+///
+/// Vectors are not bit-packed: each coefficient is still a separate byte.  The Z/2 path of [`decomp_row`](crate::decomp_row::decomp_row) and [`decomp_col`](crate::decomp_col::decomp_col) is chosen by the ring (`RingSpec::Modulus(2)`), not by the coefficient type, so it works equally well with `i16` coefficients; this type only makes the stored coefficients smaller.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Z2Bool(pub bool);
+
+/// Implimentation of trait RingElement for the two element field
+impl RingElement for Z2Bool {
+    fn represents(ringspec: &RingSpec) -> bool { matches!(ringspec, RingSpec::Modulus(2)) }
+
+    fn add_in(&self, other: &Self, ringspec: &RingSpec) -> Self {
+        match ringspec {
+            RingSpec::Modulus(2) => Z2Bool(self.0 ^ other.0),
+            _ => unsupported_ring(ringspec, "Z2Bool")
+        }
+    }
+
+    fn multiply_in(&self, other: &Self, ringspec: &RingSpec) -> Self {
+        match ringspec {
+            RingSpec::Modulus(2) => Z2Bool(self.0 & other.0),
+            _ => unsupported_ring(ringspec, "Z2Bool")
+        }
+    }
+
+    fn negate_in(&self, ringspec: &RingSpec) -> Self {
+        match ringspec {
+            RingSpec::Modulus(2) => *self,
+            _ => unsupported_ring(ringspec, "Z2Bool")
+        }
+    }
+
+    fn is_zero_in(&self, ringspec: &RingSpec) -> bool {
+        match ringspec {
+            RingSpec::Modulus(2) => !self.0,
+            _ => unsupported_ring(ringspec, "Z2Bool")
+        }
+    }
+
+    fn invert_in(&self, ringspec: &RingSpec) -> Option<Self> {
+        match ringspec {
+            RingSpec::Modulus(2) => if self.0 { Some(*self) } else { None },
+            _ => unsupported_ring(ringspec, "Z2Bool")
+        }
+    }
+}
+
+/// Methods of RingMetadata
+impl<ElementType: Clone> RingMetadata<ElementType> {
+
+    /// True if the ring is the two element field Z/2Z.
+    ///
+    /// Over Z/2Z every nonzero coefficient equals 1, so adding one sparse vector to another amounts to taking the symmetric difference of their supports; the decomposition routines use this to skip multiplications and inversions.
+    pub fn is_binary(&self) -> bool {
+        matches!(self.ringspec, RingSpec::Modulus(2))
+    }
+
+    /// True if every nonzero element of the ring is invertible, that is, if the ring is Z/pZ for a prime p, the rationals, or the reals.
+    ///
+    /// The elimination routines (e.g. [`decomp_row`](crate::decomp_row::decomp_row)) divide by pivots, so they require a field; over the integers, use [`decomp_pid`](crate::decomp_pid::decomp_pid) instead.
+    pub fn is_field(&self) -> bool {
+        match self.ringspec {
+            RingSpec::Integer => false,
+            RingSpec::Modulus(modulus) => modulus >= 2 && (2..).take_while(|divisor| divisor * divisor <= modulus).all(|divisor| modulus % divisor != 0),
+            RingSpec::Rational | RingSpec::Float(_) => true,
+        }
+    }
+}
+
+/// Methods of RingMetadata
+impl<ElementType: RingElement> RingMetadata<ElementType> {
+
+    /// Check that the elimination routines (e.g. [`decomp_row`](crate::decomp_row::decomp_row)) can run over this ring: the coefficient type must be able to represent the ring (see [`RingElement::represents`](RingElement::represents)), and the ring must be a field (see [`is_field`](RingMetadata::is_field)).
+    pub fn check_field(&self) -> Result<(), DecompError> {
+        if !ElementType::represents(&self.ringspec) {
+            return Err(DecompError::UnsupportedRing(self.ringspec.clone()));
+        }
+        if !self.is_field() {
+            return Err(DecompError::NotAField(self.ringspec.clone()));
         }
+        Ok(())
     }
+}
+
+/// An error returned by the elimination routines, such as [`decomp_row`](crate::decomp_row::decomp_row)
+///
+/// ```
+/// use exhact::matrix::{RingSpec, RingMetadata, MajorDimension, DecompError};
+/// use exhact::csm::CSM;
+/// use exhact::decomp_row::decomp_row;
+///
+/// // the 1x1 matrix [2] over the integers; 2 is not invertible, so the ring is rejected before elimination starts
+/// let ringmetadata = RingMetadata{ ringspec: RingSpec::Integer, identity_additive: 0i16, identity_multiplicative: 1i16 };
+/// let mut matrix = CSM::new(MajorDimension::Row, ringmetadata);
+/// matrix.append_maj(&mut vec![(0, 2)].into_iter().collect());
+/// let result = decomp_row(&matrix, &mut vec![0]);
+/// assert!(result.err() == Some(DecompError::NotAField(RingSpec::Integer)));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum DecompError {
+    /// The coefficient ring is not a field (see [`RingMetadata::is_field`](RingMetadata::is_field)), so pivots may fail to be invertible; over the integers, use [`decomp_pid`](crate::decomp_pid::decomp_pid) instead
+    NotAField(RingSpec),
+    /// The coefficient type cannot represent the elements of the ring (see [`RingElement::represents`](RingElement::represents)); for example, `RingSpec::Modulus(n)` with coefficients of type `i16` and `n > 32768`
+    UnsupportedRing(RingSpec),
+    /// The matrix oracle is column-major, but the routine needs a row-major oracle; wrap it in [`Transposed`](Transposed) to factor the transpose instead
+    ColumnMajorOracle,
+    /// The matrix has a pivot in a column whose minor key (written in `Debug` format) was not listed among the minor keys of the matrix
+    PivotOutsideMinKeys(String),
+    /// The rank of the matrix exceeds the number of listed minor keys, so some pivot lies in a column that was not listed
+    RankExceedsMinKeys{ rank: usize, num_min_keys: usize },
+}
+
+/// The output of the elimination routines, such as [`decomp_row`](crate::decomp_row::decomp_row): a row operation matrix and an [`Indexing`](crate::chx::Indexing) that records the matching, or a [`DecompError`](DecompError)
+pub type DecompResult<MinKey, MajKey, SnzVal> = Result<(CSM<usize, SnzVal>, Indexing<MinKey, MajKey>), DecompError>;
+
+/// A [`DecompResult`](DecompResult) with an extra piece of output, such as the [`PivotReport`](crate::decomp_row::PivotReport) of [`decomp_row_with_pivot_threshold`](crate::decomp_row::decomp_row_with_pivot_threshold)
+pub type DecompResultWith<MinKey, MajKey, SnzVal, Extra> = Result<(CSM<usize, SnzVal>, Indexing<MinKey, MajKey>, Extra), DecompError>;
+
+/// Ring operations for sparse matrix algorithms: addition, multiplication, and identity elements.
+pub trait Semiring<Element> {
+
+    /// The additive identity
+    fn zero(&self) -> Element;
+
+    /// The multiplicative identity
+    fn one(&self) -> Element;
 
     /// Determine whether x is 0 in this ring.
-    pub fn is_0( &self, x: &ElementType ) -> bool {
-        match self.inverse(x) {
-            Some(_) => { return false; }
-            None => { return true; }
+    fn is_0(&self, x: &Element) -> bool;
+
+    /// The sum x + y
+    fn add(&self, x: &Element, y: &Element) -> Element;
+
+    /// The product x * y
+    fn multiply(&self, x: &Element, y: &Element) -> Element;
+
+    /// Simplify the representation of x. For example, 6 in Modulus(5) is simplified to 1.
+    fn simplify(&self, x: &Element) -> Element;
+}
+
+/// A [`Semiring`](Semiring) with additive inverses.
+pub trait Ring<Element>: Semiring<Element> {
+
+    /// The additive inverse -x
+    fn negate(&self, x: &Element) -> Element;
+
+    /// The difference x - y
+    fn subtract(&self, x: &Element, y: &Element) -> Element {
+        self.add(x, &self.negate(y))
+    }
+}
+
+/// A [`Ring`](Ring) in which we can (try to) invert elements.
+///
+/// For fields every nonzero element is invertible; for other rings (e.g. the integers) only the units are.
+pub trait DivisionRing<Element>: Ring<Element> {
+
+    /// Find the inverse of x and returns None if does not exist
+    fn inverse(&self, x: &Element) -> Option<Element>;
+
+    /// The quotient x / y, or None if y is not invertible
+    fn divide(&self, x: &Element, y: &Element) -> Option<Element> {
+        self.inverse(y).map(|inverse| self.multiply(x, &inverse))
+    }
+}
+
+/// A [`Ring`](Ring) with division with remainder, and hence greatest common divisors.
+pub trait EuclideanRing<Element: Clone>: Ring<Element> {
+
+    /// A pair `(q, r)` such that `x = q * y + r`, with `r` "smaller" than `y`
+    fn divide_with_remainder(&self, x: &Element, y: &Element) -> (Element, Element);
+
+    /// A unit `u` such that `u * x` is the preferred representative of the associates of x (e.g. `|x|` for integers)
+    fn normal_unit(&self, x: &Element) -> Element;
+
+    /// The preferred representative of the associates of x
+    fn normalize(&self, x: &Element) -> Element {
+        self.multiply(&self.normal_unit(x), x)
+    }
+
+    /// A triple `(g, s, t)` such that `g` is a (normalized) greatest common divisor of x and y and `s * x + t * y = g`
+    fn gcd_bezout(&self, x: &Element, y: &Element) -> (Element, Element, Element) {
+        let (mut r0, mut s0, mut t0) = (x.clone(), self.one(), self.zero());
+        let (mut r1, mut s1, mut t1) = (y.clone(), self.zero(), self.one());
+        while !self.is_0(&r1) {
+            let (quotient, remainder) = self.divide_with_remainder(&r0, &r1);
+            let s2 = self.subtract(&s0, &self.multiply(&quotient, &s1));
+            let t2 = self.subtract(&t0, &self.multiply(&quotient, &t1));
+            r0 = std::mem::replace(&mut r1, remainder);
+            s0 = std::mem::replace(&mut s1, s2);
+            t0 = std::mem::replace(&mut t1, t2);
         }
+        let unit = self.normal_unit(&r0);
+        (self.multiply(&unit, &r0), self.multiply(&unit, &s0), self.multiply(&unit, &t0))
     }
+}
+
+impl<ElementType: RingElement> Semiring<ElementType> for RingMetadata<ElementType> {
+    fn zero(&self) -> ElementType { self.identity_additive.clone() }
+
+    fn one(&self) -> ElementType { self.identity_multiplicative.clone() }
+
+    fn is_0(&self, x: &ElementType) -> bool { x.is_zero_in(&self.ringspec) }
+
+    fn add(&self, x: &ElementType, y: &ElementType) -> ElementType { x.add_in(y, &self.ringspec) }
 
+    fn multiply(&self, x: &ElementType, y: &ElementType) -> ElementType { x.multiply_in(y, &self.ringspec) }
+
+    fn simplify(&self, x: &ElementType) -> ElementType { x.simplify_in(&self.ringspec) }
+}
+
+impl<ElementType: RingElement> Ring<ElementType> for RingMetadata<ElementType> {
+    fn negate(&self, x: &ElementType) -> ElementType { x.negate_in(&self.ringspec) }
+}
+
+impl<ElementType: EuclideanElement> EuclideanRing<ElementType> for RingMetadata<ElementType> {
+    fn divide_with_remainder(&self, x: &ElementType, y: &ElementType) -> (ElementType, ElementType) { x.divide_with_remainder_in(y, &self.ringspec) }
+
+    fn normal_unit(&self, x: &ElementType) -> ElementType { x.normal_unit_in(&self.ringspec) }
+}
+
+impl<ElementType: RingElement> DivisionRing<ElementType> for RingMetadata<ElementType> {
+    fn inverse(&self, x: &ElementType) -> Option<ElementType> {
+        if self.is_0(x) { return None; }
+        x.invert_in(&self.ringspec)
+    }
 }
 
 
@@ -290,29 +897,193 @@ impl<Key: Debug, Val: Debug> SparseVector<Key, Val>{
     }
 }
 
-// Given an index function f: I -> X, this enum offers several different data formats for representing the set I (that is, the domain of the index function f).  If the user chooses a vector format, then all pairs of elements in the vector should be distinct.
-enum IndexDomain<IndexType>{
+/// Algebraic operations on SparseVector
+///
+/// The operations in this block assume (and preserve) the invariant that `ind` is sorted in strictly ascending order and `snz` contains no zero entries.  Use [`from_itr`](SparseVector::from_itr) or [`from_hash`](SparseVector::from_hash) to put an arbitrary collection of entries into this form.
+impl<Key, Val> SparseVector<Key, Val> where
+Key: Ord + Eq + Hash + Clone,
+Val: RingElement
+{
+    /// Construct a sparse vector from an iterator of entries, which may be unsorted and contain repeated keys; repeated keys are summed and zero entries are dropped.
+    pub fn from_itr<I: Iterator<Item=(Key, Val)>>( ringmetadata: &RingMetadata<Val>, iterator: I ) -> SparseVector<Key, Val> {
+        let mut merge = MergeItr::new(ringmetadata.clone());
+        merge.push(iterator, ringmetadata.identity_multiplicative.clone());
+        let mut vector = SparseVector{ ind: Vec::new(), snz: Vec::new() };
+        for (key, val) in merge {
+            vector.ind.push(key);
+            vector.snz.push(val);
+        }
+        vector
+    }
+
+    /// Construct a sparse vector from a hash map, in the format used by the [`solver`](crate::solver) module.
+    pub fn from_hash( ringmetadata: &RingMetadata<Val>, hash: HashMap<Key, Val> ) -> SparseVector<Key, Val> {
+        SparseVector::from_itr(ringmetadata, hash.into_iter())
+    }
+
+    /// Convert to a hash map, in the format used by the [`solver`](crate::solver) module.
+    pub fn into_hash( self ) -> HashMap<Key, Val> {
+        self.ind.into_iter().zip(self.snz).collect()
+    }
+
+    /// An iterator that runs over the entries of the vector, in ascending order of keys.
+    pub fn iter( &self ) -> impl Iterator<Item=(Key, Val)> + '_ {
+        self.ind.iter().cloned().zip(self.snz.iter().cloned())
+    }
+
+    /// Returns `self + other`
+    pub fn add( &self, ringmetadata: &RingMetadata<Val>, other: &SparseVector<Key, Val> ) -> SparseVector<Key, Val> {
+        sum_vector(ringmetadata, self.iter(), other.iter())
+    }
+
+    /// Returns `scalar * self`
+    pub fn scale( &self, ringmetadata: &RingMetadata<Val>, scalar: &Val ) -> SparseVector<Key, Val> {
+        let mut vector = SparseVector{ ind: Vec::new(), snz: Vec::new() };
+        for (key, val) in self.iter() {
+            let val = ringmetadata.multiply(scalar, &val);
+            if ringmetadata.is_0(&val) { continue; }
+            vector.ind.push(key);
+            vector.snz.push(val);
+        }
+        vector
+    }
+
+    /// Returns the dot product of `self` and `other`
+    pub fn dot( &self, ringmetadata: &RingMetadata<Val>, other: &SparseVector<Key, Val> ) -> Val {
+        let mut product = ringmetadata.identity_additive.clone();
+        let (mut ii, mut jj) = (0, 0);
+        while ii < self.ind.len() && jj < other.ind.len() {
+            match self.ind[ii].cmp(&other.ind[jj]) {
+                Ordering::Less => { ii += 1; }
+                Ordering::Greater => { jj += 1; }
+                Ordering::Equal => {
+                    product = ringmetadata.add(&product, &ringmetadata.multiply(&self.snz[ii], &other.snz[jj]));
+                    ii += 1;
+                    jj += 1;
+                }
+            }
+        }
+        ringmetadata.simplify(&product)
+    }
+}
+
+/// Given an index function f: I -> X, this enum offers several different data formats for representing the set I (that is, the domain of the index function f).  If the user chooses a vector format, then all pairs of elements in the vector should be distinct.
+pub enum IndexDomain<IndexType>{
     Range(Range<IndexType>),  // example: 0..4 means that the index set is [0, 1, 2, 3]
     Vec(Vec<IndexType>),      // example: [0, 1, 2, 3]
     Hashset(HashSet<IndexType>),
-    Iterator(Box<dyn Iterator<Item=IndexType>>)   // a custom option
+    Iterator(Box<dyn Fn() -> Box<dyn Iterator<Item=IndexType>>>),   // a custom option; the function should return a fresh iterator each time it is called
+    Predicate(Box<dyn Fn(&IndexType) -> bool>)  // the set of keys for which the predicate returns true
+}
+
+/// Methods of IndexDomain
+impl<IndexType> IndexDomain<IndexType> where
+IndexType: PartialOrd + Eq + Hash + Clone
+{
+    /// Determine whether `key` lies in the domain.
+    pub fn contains( &self, key: &IndexType ) -> bool {
+        match self {
+            IndexDomain::Range(range) => range.contains(key),
+            IndexDomain::Vec(vec) => vec.contains(key),
+            IndexDomain::Hashset(set) => set.contains(key),
+            IndexDomain::Iterator(itr) => itr().any(|x| x == *key),
+            IndexDomain::Predicate(predicate) => predicate(key)
+        }
+    }
+
+    /// An iterator that runs over the elements of the domain, or `None` if the elements cannot be enumerated.
+    ///
+    /// The elements of a `Range` cannot be enumerated for a general key type, so this function returns `None` in that case (and likewise for a `Predicate`).  Use a `Vec` if you need to enumerate the domain.
+    pub fn keys( &self ) -> Option<Box<dyn Iterator<Item=IndexType> + '_>> {
+        match self {
+            IndexDomain::Range(_) => None,
+            IndexDomain::Vec(vec) => Some(Box::new(vec.iter().cloned())),
+            IndexDomain::Hashset(set) => Some(Box::new(set.iter().cloned())),
+            IndexDomain::Iterator(itr) => Some(itr()),
+            IndexDomain::Predicate(_) => None
+        }
+    }
 }
 
-// This enum specifies an index function f: I -> I', where I is an index set with elements of type Key, and I' is another set, with elements of type I'.
-enum IndexFunction<Key, Val> {
-    Identity, // represents the identity function
-    Function(Box<dyn Fn(Key) -> Val>), // an actual Rust function
+/// A boxed partial function, as stored in [`IndexFunction::Function`](IndexFunction::Function)
+pub type PartialFn<Key, Val> = Box<dyn Fn(&Key) -> Option<Val>>;
+
+/// A boxed function that returns the inverse image of a key, as stored in [`IndexInvImg::Function`](IndexInvImg::Function)
+pub type InvImgFn<Key, Val> = Box<dyn Fn(&Key) -> Vec<Val>>;
+
+/// This enum specifies an index function f: I -> I', where I is an index set with elements of type Key, and I' is another set, with elements of type Val.
+///
+/// The function may be partial; it returns `None` for keys outside its domain.  Use [`identity`](IndexFunction::identity) for the identity function, and [`from_vec`](IndexFunction::from_vec) for a function of form i |-> vec[i].
+pub enum IndexFunction<Key, Val> {
+    Function(PartialFn<Key, Val>), // an actual Rust function
     Hashmap(HashMap<Key, Val>), // a function recorded as a hash map
-    Vec(Vec<Val>) // a function of form i |-> vec[i]
 }
+
+/// Methods of IndexFunction
+impl<Key, Val> IndexFunction<Key, Val> where
+Key: Eq + Hash,
+Val: Clone
+{
+    /// Evaluate the function at `key`, returning `None` if `key` lies outside the domain.
+    pub fn eval( &self, key: &Key ) -> Option<Val> {
+        match self {
+            IndexFunction::Function(fun) => fun(key),
+            IndexFunction::Hashmap(hash) => hash.get(key).cloned()
+        }
+    }
+}
+
+/// Methods of IndexFunction
+impl<Key: Clone + 'static> IndexFunction<Key, Key> {
+    /// The identity function
+    pub fn identity() -> IndexFunction<Key, Key> {
+        IndexFunction::Function(Box::new(|x| Some(x.clone())))
+    }
+}
+
+/// Methods of IndexFunction
+impl<Val: Clone + 'static> IndexFunction<usize, Val> {
+    /// A function of form i |-> vec[i]; for example, the `index_2_majkey` field of an [`Indexing`](crate::chx::Indexing).
+    pub fn from_vec( vec: Vec<Val> ) -> IndexFunction<usize, Val> {
+        IndexFunction::Function(Box::new(move |i| vec.get(*i).cloned()))
+    }
+}
+
 // Original : M(I,J), f: I'-> I, g: J'-> J
 //
-// Suppose we have a matrix M: IxJ -> Field.  We have a function f: J' -> J, and we'd like to create a sparse matrix representation of the matrix N: IxJ -> Field defined by N[i,j] = M[i, f(j)].  If the majs of our matrix are stored as vectors of tuples [(col_index, entry), ..., (col_index, entry)] then we need to create a pair (col_index', entry) for every col_index' in J' such that f(col_index') = col_index.  In essence, we need to compute the inverse image of col_index under f.
-enum IndexInvImg<Key, Val> {
-    Identity, // represents the identity function
-    Function(Box<dyn Fn(Key) -> Vec<Val>>), // an actual Rust function
+/// Suppose we have a matrix M: IxJ -> Field.  We have a function f: J' -> J, and we'd like to create a sparse matrix representation of the matrix N: IxJ' -> Field defined by N[i,j'] = M[i, f(j')].  If the majs of our matrix are stored as vectors of tuples [(col_index, entry), ..., (col_index, entry)] then we need to create a pair (col_index', entry) for every col_index' in J' such that f(col_index') = col_index.  In essence, we need to compute the inverse image of col_index under f.
+///
+/// This enum records the inverse image function J -> (subsets of J').  Here `Key` is the type of J and `Val` is the type of J'.
+pub enum IndexInvImg<Key, Val> {
+    Function(InvImgFn<Key, Val>), // an actual Rust function
     Hashmap(HashMap<Key, Vec<Val>>), // a function recorded as a hash map
-    //vec(Vec<Val>) // a function of form i |-> vec[i]
+}
+
+/// Methods of IndexInvImg
+impl<Key, Val> IndexInvImg<Key, Val> where
+Key: Eq + Hash + Clone,
+Val: Clone
+{
+    /// The inverse image of `key` (empty if `key` has no preimage)
+    pub fn eval( &self, key: &Key ) -> Vec<Val> {
+        match self {
+            IndexInvImg::Function(fun) => fun(key),
+            IndexInvImg::Hashmap(hash) => hash.get(key).cloned().unwrap_or_default()
+        }
+    }
+
+    /// The inverse image function of an injective function recorded as a hash map `key -> value`; for example, the `majkey_2_index` field of an [`Indexing`](crate::chx::Indexing).
+    pub fn from_injection( hash: &HashMap<Key, Val> ) -> IndexInvImg<Key, Val> {
+        IndexInvImg::Hashmap(hash.iter().map(|(key, val)| (key.clone(), vec![val.clone()])).collect())
+    }
+}
+
+/// Methods of IndexInvImg
+impl<Key: Clone + 'static> IndexInvImg<Key, Key> {
+    /// The inverse image function of the identity function
+    pub fn identity() -> IndexInvImg<Key, Key> {
+        IndexInvImg::Function(Box::new(|x| vec![x.clone()]))
+    }
 }
 
 // -----------------------------------------------------------------------------------------------
@@ -382,19 +1153,6 @@ enum IndexInvImg<Key, Val> {
 // - DISCUSSION: THESE CAN BE QUITE HANDY, AND WOULD BE FAIRLY EASY TO IMPLEMENT.  IF WE DO THIS, THEN ONE OF THE MAIN TRICKS WILL BE FIGURING OUT HOW TO PREVENT THE SYSTEM FROM MULTIPLYING THINGS BY 1 UNNECESARILY.
 
 
-// ORACLE TYPES
-// ------------
-
-// This enum lists all the structs that implement the SmOracle trait.
-// The lazy_product struct doesn't contain much data, just a sequence of references to matrix SmOracles [M0, ..., Mn].  Suppose these are row-major oracles, and the user wants row k.  Then the struct will get row k from SmOracle M0, then multiply this row by M2, ..., Mn, and return the resulting vector to the user.
-// The lazy_sum struct behaves similarly.
-enum OracleKind</*MajKey,*/ MinKey, SnzVal: Clone>{
-    Csm(CSM<MinKey, SnzVal>), // classic csr format
-    //Clique(CliqueBoundaryMatrix<Simplex, Simplex, i32>), // ripser style matrix oracle
-    //lazy_product(Oracle_Product<MajKey, MinKey, SnzVal>), // this implements lazy evaluation of product
-    //lazy_sum(Oracle_Sum<MajKey, MinKey, SnzVal>) // this implements lazy evaluation of sum
-}
-
 // ORACLE TRAIT
 // ------------
 
@@ -619,6 +1377,16 @@ SnzVal: Clone
 		None
 	}
 
+    /// True if `maj_itr` is guaranteed to return entries in ascending order of minor key.  Lazy products and sums use this to merge major fields without sorting them first.
+    fn maj_itr_sorted(&self) -> bool {
+        false
+    }
+
+    /// True if `min_itr` is guaranteed to return entries in ascending order of major key.  Lazy products and sums use this to merge minor fields without sorting them first.
+    fn min_itr_sorted(&self) -> bool {
+        false
+    }
+
     /// True if every minor field has finitely many structural nonzero entries
 	fn finiteminors(&self) -> Option<bool> {
 		Some(true)
@@ -641,6 +1409,47 @@ SnzVal: Clone
 }
 
 
+// A reference to an oracle is an oracle; this allows wrappers such as `OracleProduct` to hold references rather than owned data.
+impl<MajKey, MinKey, SnzVal, Matrix> SmOracle<MajKey, MinKey, SnzVal> for &Matrix where
+MajKey: PartialEq + Eq + Hash + Clone,
+MinKey: PartialEq + Eq + Hash + Clone,
+SnzVal: Clone,
+Matrix: SmOracle<MajKey, MinKey, SnzVal> + ?Sized
+{
+    fn ring( &self ) -> &RingMetadata<SnzVal> { (**self).ring() }
+
+    fn maj_dim( &self ) -> MajorDimension { (**self).maj_dim() }
+
+    fn maj_itr( &self, majkey: &MajKey ) -> Box<dyn Iterator<Item=(MinKey, SnzVal)> + '_> { (**self).maj_itr(majkey) }
+
+    fn min_itr( &self, minkey: &MinKey ) -> Box<dyn Iterator<Item=(MajKey, SnzVal)> + '_> { (**self).min_itr(minkey) }
+
+    fn maj_hash( &self, majkey: &MajKey ) -> HashMap<MinKey, SnzVal> { (**self).maj_hash(majkey) }
+
+    fn min_hash( &self, minkey: &MinKey ) -> HashMap<MajKey, SnzVal> { (**self).min_hash(minkey) }
+
+    fn entry( &self, majkey: &MajKey, minkey: &MinKey ) -> Option<SnzVal> { (**self).entry(majkey, minkey) }
+
+    fn maj_length( &self, majkey: &MajKey ) -> usize { (**self).maj_length(majkey) }
+
+    fn min_length( &self, minkey: &MinKey ) -> usize { (**self).min_length(minkey) }
+
+    fn countsnz( &self ) -> Option<usize> { (**self).countsnz() }
+
+    fn maj_itr_sorted( &self ) -> bool { (**self).maj_itr_sorted() }
+
+    fn min_itr_sorted( &self ) -> bool { (**self).min_itr_sorted() }
+
+    fn finiteminors( &self ) -> Option<bool> { (**self).finiteminors() }
+
+    fn finitemajors( &self ) -> Option<bool> { (**self).finitemajors() }
+
+    fn is_pivot( &self, majkey: &MajKey, minkey: &MinKey ) -> Option<bool> { (**self).is_pivot(majkey, minkey) }
+
+    fn is_apparent( &self, minkey: &MinKey ) -> Option<MajKey> { (**self).is_apparent(minkey) }
+}
+
+
 // -----------------------------------------------------------------------------------------------
 // INDEXED SPARSE MATRIX ORACLES (IndexedSmo)
 // -----------------------------------------------------------------------------------------------
@@ -649,12 +1458,67 @@ SnzVal: Clone
 // A struct that indexes into a sparse matrix oracle (SMO).
 // This struct should implement the SmOracle trait.
 /// Sparse matrix oracle that indexes into another sparse matrix oracle
-pub struct IndexedSmo<'a, MajKeyOuter, MajKeyInner, MinKeyOuter, MinKeyInner, SnzVal: Clone, IndexType>{
-    oracle: &'a OracleKind</*MajKeyInner,*/ MinKeyInner, SnzVal>,   // a reference to a SmOracle
-    indexmaj_dom: Option<Box<IndexDomain<IndexType>>>,  // the range of legal maj indices
-    indexmaj_fun: Option<IndexFunction<MajKeyOuter, MajKeyInner>>, // an object implementing the maj index function
-    indexmin_invimg: Option<IndexInvImg<MinKeyOuter, MinKeyInner>>,  // we need to be able to compute the inverse image of the minumn index function.  See the comment above the IndexInvImg enum for further details.
-    scalefactor: Option<SnzVal>, // records whether we should scale the matrix, and if so, by what sclalar
+///
+/// If `M` is the inner oracle, `f` is `indexmaj_fun` and `g` is `indexmin_fun`, then this oracle represents the matrix `N[i,j] = scalefactor * M[f(i), g(j)]`, restricted to rows `i` in `indexmaj_dom` and columns `j` in `indexmin_dom` (a domain of `None` means "no restriction").  To compute major fields we need the inverse image of `g` (stored in `indexmin_invimg`), and to compute minor fields we need the inverse image of `f` (stored in `indexmaj_invimg`).
+///
+/// For example, to relabel the simplices of a boundary matrix by the integers in an [`Indexing`](crate::chx::Indexing), one can set `indexmaj_fun = IndexFunction::from_vec(indexing.index_2_majkey.clone())` and `indexmaj_invimg = IndexInvImg::from_injection(&indexing.majkey_2_index)`.
+///
+/// ```
+/// use exhact::matrix::{RingSpec, RingMetadata, MajorDimension, SmOracle, IndexedSmo, IndexDomain, IndexFunction, IndexInvImg};
+/// use exhact::csm::CSM;
+/// use std::collections::HashMap;
+///
+/// // a 2x3 matrix over the field of order 5, with columns labeled 'a', 'b', 'c'
+/// //
+/// //     a b c
+/// // 0 [ 1 0 2 ]
+/// // 1 [ 0 3 0 ]
+/// let ringmetadata = RingMetadata{ ringspec: RingSpec::Modulus(5), identity_additive: 0i16, identity_multiplicative: 1i16 };
+/// let mut matrix = CSM::new(MajorDimension::Row, ringmetadata);
+/// for row in vec![ vec![('a', 1), ('c', 2)], vec![('b', 3)] ] {
+///     for (col, val) in row { matrix.push_snzval(col, val); }
+///     matrix.majptr.push(matrix.minind.len());
+///     matrix.nummaj += 1;
+/// }
+///
+/// // swap the rows, relabel the columns 0, 1, 2, and scale by 2
+/// let row_labels = vec![1, 0];                                        // outer row -> inner row
+/// let col_labels = vec!['a', 'b', 'c'];                               // outer column -> inner column
+/// let row_index: HashMap<usize, usize> = vec![(1, 0), (0, 1)].into_iter().collect();
+/// let col_index: HashMap<char, usize> = vec![('a', 0), ('b', 1), ('c', 2)].into_iter().collect();
+/// let relabeled = IndexedSmo{
+///     oracle: &matrix,
+///     indexmaj_dom: Some(IndexDomain::Vec(vec![0, 1])),
+///     indexmin_dom: None,
+///     indexmaj_fun: IndexFunction::from_vec(row_labels),
+///     indexmin_fun: IndexFunction::from_vec(col_labels),
+///     indexmaj_invimg: IndexInvImg::from_injection(&row_index),
+///     indexmin_invimg: IndexInvImg::from_injection(&col_index),
+///     scalefactor: Some(2),
+/// };
+///
+/// //     0 1 2
+/// // 0 [ 0 1 0 ]
+/// // 1 [ 2 0 4 ]
+/// assert_eq!(relabeled.maj_itr(&0).collect::<Vec<_>>(), vec![(1, 1)]);
+/// assert_eq!(relabeled.maj_itr(&1).collect::<Vec<_>>(), vec![(0, 2), (2, 4)]);
+/// assert_eq!(relabeled.min_itr(&2).collect::<Vec<_>>(), vec![(1, 4)]);
+/// assert_eq!(relabeled.min_itr(&1).collect::<Vec<_>>(), vec![(0, 1)]);
+///
+/// let csm = relabeled.into_csm().unwrap();
+/// assert_eq!(csm.majptr, vec![0, 1, 3]);
+/// assert_eq!(csm.minind, vec![1, 0, 2]);
+/// assert_eq!(csm.snzval, vec![1, 2, 4]);
+/// ```
+pub struct IndexedSmo<'a, MajKeyOuter, MajKeyInner, MinKeyOuter, MinKeyInner, SnzVal: Clone>{
+    pub oracle: &'a dyn SmOracle<MajKeyInner, MinKeyInner, SnzVal>,   // a reference to a SmOracle
+    pub indexmaj_dom: Option<IndexDomain<MajKeyOuter>>,  // the range of legal maj indices
+    pub indexmin_dom: Option<IndexDomain<MinKeyOuter>>,  // the range of legal min indices
+    pub indexmaj_fun: IndexFunction<MajKeyOuter, MajKeyInner>, // an object implementing the maj index function
+    pub indexmin_fun: IndexFunction<MinKeyOuter, MinKeyInner>, // an object implementing the min index function
+    pub indexmaj_invimg: IndexInvImg<MajKeyInner, MajKeyOuter>,  // inverse image of the maj index function; needed to compute minor fields
+    pub indexmin_invimg: IndexInvImg<MinKeyInner, MinKeyOuter>,  // we need to be able to compute the inverse image of the minumn index function.  See the comment above the IndexInvImg enum for further details.
+    pub scalefactor: Option<SnzVal>, // records whether we should scale the matrix, and if so, by what sclalar
 
     // I added this attributes earlier but now I think they should not be part of this struct.  The user would have to set these values themselves, and if they got it wrong then there could be major consequences.
     //If a user really wants to record the number of majs, then they can implement the ExactIterator trait on their iterator (or perhaps we could add an attribute to IndexDomain to record the length of the iterator).
@@ -663,72 +1527,136 @@ pub struct IndexedSmo<'a, MajKeyOuter, MajKeyInner, MinKeyOuter, MinKeyInner, Sn
     // numsnz: Option<usize>  // number of structural nonzeros (optional)
 }
 
-impl<'a, MajKeyOuter, MajKeyInner, MinKeyOuter, MinKeyInner, SnzVal: Clone, IndexType>
-    IndexedSmo<'a, MajKeyOuter, MajKeyInner, MinKeyOuter, MinKeyInner, SnzVal, IndexType>{
-
-    /// Returns an iterator that runs over all the major key values we want to index.  If we think of a IndexedSmo as a submatrix M[I,J], then `majkey_iterator` is like the set I.
-    fn majkey_iterator( &self ) {} //-> Box<dyn Iterator<MajKeyOuter>>
-
-    /// This function exports the IndexedSmo to a CSR SmOracle.
-    fn IntoCSM( &self ) {} /* -> CSM {
-
-        // Calculate the number of majs
-        nummaj = //
-
-        // Initialize the majptr
-        majptr = Vec::with_capacity( nummaj + 1 ) // this might be a bad way to initialize ... i don't know
-
-        // Get an iterator that will run over all the major indices.
-        majkey_iterator = self.majkey_iterator()
-
-        // Create a hashmap from keys to integers.
-        majkey_hash = HashMap<MakKey_outer, usize>
-
-        // Use the iterator in a for-loop to calculate the majptr vector
-        for (slice_number, maj_key) in enumerate(majkey_iterator){
-            majkey_hash.push(maj_key, slice_number)
-            numsnz_thismaj = // this might be tricky to calculate ... unless this Port uses all the columns that the oracle provides, we'll have to count just the ones that the Port asks for (possibly multiple times, depending on indexrel_min)
+/// Methods of IndexedSmo
+impl<'a, MajKey, MinKey, SnzVal> IndexedSmo<'a, MajKey, MajKey, MinKey, MinKey, SnzVal> where
+MajKey: Clone + 'static,
+MinKey: Clone + 'static,
+SnzVal: Clone
+{
+    /// Wrap an oracle without relabeling its keys.  The domains and scale factor can be set afterward, by modifying the corresponding fields.
+    pub fn new( oracle: &'a dyn SmOracle<MajKey, MinKey, SnzVal> ) -> IndexedSmo<'a, MajKey, MajKey, MinKey, MinKey, SnzVal> {
+        IndexedSmo{
+            oracle,
+            indexmaj_dom: None,
+            indexmin_dom: None,
+            indexmaj_fun: IndexFunction::identity(),
+            indexmin_fun: IndexFunction::identity(),
+            indexmaj_invimg: IndexInvImg::identity(),
+            indexmin_invimg: IndexInvImg::identity(),
+            scalefactor: None
         }
+    }
+}
 
-        // Initialize the minind and snzval vectors
-        numsnz = majkey_iterator.last()
-        minind = Vec::with_capacity(numsnz)
-        snzval = Vec::with_capacity(numsnz) // not sure how to make this initialize as a vector with entries of the proper type
+/// Methods of IndexedSmo
+impl<'a, MajKeyOuter, MajKeyInner, MinKeyOuter, MinKeyInner, SnzVal> IndexedSmo<'a, MajKeyOuter, MajKeyInner, MinKeyOuter, MinKeyInner, SnzVal> where
+MajKeyOuter: PartialEq + Eq + Hash + Clone + PartialOrd,
+MajKeyInner: PartialEq + Eq + Hash + Clone,
+MinKeyOuter: PartialEq + Eq + Hash + Clone + PartialOrd,
+MinKeyInner: PartialEq + Eq + Hash + Clone,
+SnzVal: RingElement
+{
 
-        // Fill in the minind and snzval vectors; this process will probably look a lot like the algorithm for transposing a CSR matrix
-        ..
+    /// Returns an iterator that runs over all the major key values we want to index.  If we think of a IndexedSmo as a submatrix M[I,J], then `majkey_iterator` is like the set I.
+    ///
+    /// Returns `None` if the major domain is unrestricted, or cannot be enumerated (see [`IndexDomain::keys`](IndexDomain::keys)).
+    pub fn majkey_iterator( &self ) -> Option<Box<dyn Iterator<Item=MajKeyOuter> + '_>> {
+        self.indexmaj_dom.as_ref()?.keys()
+    }
 
-        // Wrap all the calculated vectors into a new CSR struct!
-    }*/
+    /// Scale a structural nonzero by the scale factor; returns `None` if the result is zero
+    fn scale( &self, val: SnzVal ) -> Option<SnzVal> {
+        let val = match &self.scalefactor {
+            Some(scale) => self.oracle.ring().multiply(scale, &val),
+            None => val
+        };
+        if self.oracle.ring().is_0(&val) { None } else { Some(val) }
+    }
 }
 
+/// Methods of IndexedSmo
+impl<'a, MajKeyOuter, MajKeyInner, MinKeyOuter, MinKeyInner, SnzVal> IndexedSmo<'a, MajKeyOuter, MajKeyInner, MinKeyOuter, MinKeyInner, SnzVal> where
+MajKeyOuter: PartialEq + Eq + Hash + Clone + PartialOrd,
+MajKeyInner: PartialEq + Eq + Hash + Clone,
+MinKeyOuter: PartialEq + Eq + Hash + Clone + PartialOrd + Debug,
+MinKeyInner: PartialEq + Eq + Hash + Clone,
+SnzVal: RingElement + Debug
+{
+    /// This function exports the IndexedSmo to a CSM.  The major fields of the CSM appear in the order given by [`majkey_iterator`](IndexedSmo::majkey_iterator); returns `None` if this iterator is unavailable.
+    pub fn into_csm( &self ) -> Option<CSM<MinKeyOuter, SnzVal>> {
+        let mut csm = CSM::new(self.maj_dim(), self.oracle.ring().clone());
+        for majkey in self.majkey_iterator()? {
+            for (minkey, val) in self.maj_itr(&majkey) {
+                csm.push_snzval(minkey, val);
+            }
+            csm.majptr.push(csm.minind.len());
+            csm.nummaj += 1;
+        }
+        Some(csm)
+    }
+}
 
 
-// Add later
-/*
-impl<'a, MajKeyOuter, MajKeyInner, MinKeyOuter, MinKeyInner, SnzVal, IndexType>
+// See the SmOracle trait for a description of each method
+impl<'a, MajKeyOuter, MajKeyInner, MinKeyOuter, MinKeyInner, SnzVal>
     SmOracle<MajKeyOuter, MinKeyOuter, SnzVal> for
-    IndexedSmo<'a, MajKeyOuter, MajKeyInner, MinKeyOuter, MinKeyInner, SnzVal, IndexType>{
+    IndexedSmo<'a, MajKeyOuter, MajKeyInner, MinKeyOuter, MinKeyInner, SnzVal> where
+MajKeyOuter: PartialEq + Eq + Hash + Clone + PartialOrd,
+MajKeyInner: PartialEq + Eq + Hash + Clone,
+MinKeyOuter: PartialEq + Eq + Hash + Clone + PartialOrd,
+MinKeyInner: PartialEq + Eq + Hash + Clone,
+SnzVal: RingElement
+{
+    fn ring( &self ) -> &RingMetadata<SnzVal> { self.oracle.ring() }
 
-    // We have to remember to scale the entries by `scalefactor`.
+    fn maj_dim( &self ) -> MajorDimension { self.oracle.maj_dim() }
 
-    fn maj_indmin( &self, majkey: MajKeyOuter ) -> Option<Vec<MinKeyOuter>> {
-        None
-    }
-    fn maj_snzval( &self, majkey: MajKeyOuter ) -> Option<Vec<SnzVal>> {
-        None
-    }
-    fn min_indmaj( &self, majkey: MinKeyOuter ) -> Option<Vec<MajKeyOuter>> {
-        None
+    fn maj_itr( &self, majkey: &MajKeyOuter ) -> Box<dyn Iterator<Item=(MinKeyOuter, SnzVal)> + '_> {
+        if let Some(domain) = &self.indexmaj_dom {
+            if !domain.contains(majkey) { return Box::new(std::iter::empty()); }
+        }
+        let inner = match self.indexmaj_fun.eval(majkey) {
+            Some(key) => key,
+            None => { return Box::new(std::iter::empty()); }
+        };
+        let mut field = Vec::new();
+        for (minkey, val) in self.oracle.maj_itr(&inner) {
+            let val = match self.scale(val) { Some(val) => val, None => continue };
+            for outer in self.indexmin_invimg.eval(&minkey) {
+                if let Some(domain) = &self.indexmin_dom {
+                    if !domain.contains(&outer) { continue; }
+                }
+                field.push((outer, val.clone()));
+            }
+        }
+        Box::new(field.into_iter())
     }
-    fn min_snzval( &self, majkey: MinKeyOuter ) -> Option<Vec<SnzVal>> {
-        None
+
+    fn min_itr( &self, minkey: &MinKeyOuter ) -> Box<dyn Iterator<Item=(MajKeyOuter, SnzVal)> + '_> {
+        if let Some(domain) = &self.indexmin_dom {
+            if !domain.contains(minkey) { return Box::new(std::iter::empty()); }
+        }
+        let inner = match self.indexmin_fun.eval(minkey) {
+            Some(key) => key,
+            None => { return Box::new(std::iter::empty()); }
+        };
+        let mut field = Vec::new();
+        for (majkey, val) in self.oracle.min_itr(&inner) {
+            let val = match self.scale(val) { Some(val) => val, None => continue };
+            for outer in self.indexmaj_invimg.eval(&majkey) {
+                if let Some(domain) = &self.indexmaj_dom {
+                    if !domain.contains(&outer) { continue; }
+                }
+                field.push((outer, val.clone()));
+            }
+        }
+        Box::new(field.into_iter())
     }
 
-    // ... etc.
+    fn finiteminors( &self ) -> Option<bool> { self.oracle.finiteminors() }
 
+    fn finitemajors( &self ) -> Option<bool> { self.oracle.finitemajors() }
 }
-*/
 
 
 
@@ -748,18 +1676,197 @@ impl<'a, MajKeyOuter, MajKeyInner, MinKeyOuter, MinKeyInner, SnzVal, IndexType>
 
 // TRANSPOSE FOR SMORacles
 // -----------------------
-// The author of each matrix oracle is responsible for writing their own transpose function. (In many cases, they can simply export to CSR format, then use the built-in function to transpose CSR's).
+// Any oracle can be transposed lazily by wrapping it in a `Transposed` struct (see below).  This doesn't copy any data; it just swaps the roles of major and minor fields.  If you need a transposed copy in memory, then you can export to CSR format and use the built-in function to transpose CSR's.
 
 // TRANSPOSE FOR SmoPorts
 // ----------------------
-// I am exhausted.  I don't think it's essential to implement the transpose operation for SmoPorts right now.  Let's leave it for later.
+// An IndexedSmo is an oracle, so it can be wrapped in a `Transposed` struct like any other.
+
+/// A lazy transpose of a sparse matrix oracle.
+///
+/// Major fields of the transpose are minor fields of the original oracle and vice versa, and the major dimension is flipped.  For example, if `matrix` is a row-major boundary matrix, then `Transposed::new(&matrix)` is a column-major oracle for the same boundary matrix, whose major fields are columns; it is also a row-major oracle for the coboundary matrix.  Wrapping a reference (rather than the oracle itself) makes the transpose zero-copy.
+///
+/// ```
+/// use exhact::matrix::{RingSpec, RingMetadata, MajorDimension, SmOracle, Transposed};
+/// use exhact::csm::CSM;
+///
+/// // a row-major 2x3 matrix over the field of order 7, which reports (0, 1) as a pivot
+/// //
+/// // [ 1 2 0 ]
+/// // [ 0 0 3 ]
+/// struct Pivoted( CSM<usize, i16> );
+///
+/// impl SmOracle<usize, usize, i16> for Pivoted {
+///     fn ring( &self ) -> &RingMetadata<i16> { self.0.ring() }
+///     fn maj_itr( &self, majkey: &usize ) -> Box<dyn Iterator<Item=(usize, i16)> + '_> { self.0.maj_itr(majkey) }
+///     fn min_itr( &self, minkey: &usize ) -> Box<dyn Iterator<Item=(usize, i16)> + '_> { self.0.min_itr(minkey) }
+///     fn is_pivot( &self, majkey: &usize, minkey: &usize ) -> Option<bool> { Some((*majkey, *minkey) == (0, 1)) }
+/// }
+///
+/// let ringmetadata = RingMetadata{ ringspec: RingSpec::Modulus(7), identity_additive: 0, identity_multiplicative: 1 };
+/// let mut matrix = CSM::new(MajorDimension::Row, ringmetadata);
+/// for row in vec![ vec![(0, 1), (1, 2)], vec![(2, 3)] ] {
+///     for (col, val) in row { matrix.push_snzval(col, val); }
+///     matrix.majptr.push(matrix.minind.len());
+///     matrix.nummaj += 1;
+/// }
+/// let matrix = Pivoted(matrix);
+///
+/// let transpose = Transposed::new(&matrix);
+/// assert!(transpose.maj_dim() == MajorDimension::Col);
+///
+/// // major fields of the transpose are minor fields of the matrix, and vice versa
+/// assert_eq!(transpose.maj_itr(&1).collect::<Vec<_>>(), matrix.min_itr(&1).collect::<Vec<_>>());
+/// assert_eq!(transpose.min_itr(&0).collect::<Vec<_>>(), vec![(0, 1), (1, 2)]);
+///
+/// // entries and pivots are looked up with the keys swapped
+/// assert_eq!(transpose.entry(&2, &1), Some(3));
+/// assert_eq!(transpose.entry(&0, &1), None);
+/// assert_eq!(transpose.is_pivot(&1, &0), Some(true));
+/// assert_eq!(transpose.is_pivot(&0, &1), Some(false));
+/// ```
+pub struct Transposed<Matrix>{
+    pub matrix: Matrix
+}
+
+/// Methods of Transposed
+impl<Matrix> Transposed<Matrix> {
+    /// Wrap an oracle in a lazy transpose
+    pub fn new( matrix: Matrix ) -> Transposed<Matrix> {
+        Transposed{ matrix }
+    }
+}
+
+// See the SmOracle trait for a description of each method
+impl<MajKey, MinKey, SnzVal, Matrix> SmOracle<MinKey, MajKey, SnzVal> for Transposed<Matrix> where
+MajKey: PartialEq + Eq + Hash + Clone,
+MinKey: PartialEq + Eq + Hash + Clone,
+SnzVal: Clone,
+Matrix: SmOracle<MajKey, MinKey, SnzVal>
+{
+    fn ring( &self ) -> &RingMetadata<SnzVal> { self.matrix.ring() }
+
+    fn maj_dim( &self ) -> MajorDimension {
+        match self.matrix.maj_dim() {
+            MajorDimension::Row => MajorDimension::Col,
+            MajorDimension::Col => MajorDimension::Row
+        }
+    }
+
+    fn maj_itr( &self, majkey: &MinKey ) -> Box<dyn Iterator<Item=(MajKey, SnzVal)> + '_> { self.matrix.min_itr(majkey) }
+
+    fn min_itr( &self, minkey: &MajKey ) -> Box<dyn Iterator<Item=(MinKey, SnzVal)> + '_> { self.matrix.maj_itr(minkey) }
+
+    fn maj_hash( &self, majkey: &MinKey ) -> HashMap<MajKey, SnzVal> { self.matrix.min_hash(majkey) }
+
+    fn min_hash( &self, minkey: &MajKey ) -> HashMap<MinKey, SnzVal> { self.matrix.maj_hash(minkey) }
+
+    fn entry( &self, majkey: &MinKey, minkey: &MajKey ) -> Option<SnzVal> { self.matrix.entry(minkey, majkey) }
+
+    fn maj_length( &self, majkey: &MinKey ) -> usize { self.matrix.min_length(majkey) }
+
+    fn min_length( &self, minkey: &MajKey ) -> usize { self.matrix.maj_length(minkey) }
+
+    fn countsnz( &self ) -> Option<usize> { self.matrix.countsnz() }
+
+    fn maj_itr_sorted( &self ) -> bool { self.matrix.min_itr_sorted() }
+
+    fn min_itr_sorted( &self ) -> bool { self.matrix.maj_itr_sorted() }
+
+    fn finiteminors( &self ) -> Option<bool> { self.matrix.finitemajors() }
+
+    fn finitemajors( &self ) -> Option<bool> { self.matrix.finiteminors() }
+
+    fn is_pivot( &self, majkey: &MinKey, minkey: &MajKey ) -> Option<bool> { self.matrix.is_pivot(minkey, majkey) }
+}
+
+
+// SUBMATRICES FOR SMORacles
+// -------------------------
+// To restrict an oracle to a set of major and minor keys (without relabeling them), wrap it in a `Submatrix` struct.  To relabel the keys as well, use an IndexedSmo.
+
+/// A lazy submatrix of a sparse matrix oracle.
+///
+/// This oracle represents the submatrix `M[I,J]`, where `M` is the inner oracle, `I` is the set of major keys in `majkeys`, and `J` is the set of minor keys in `minkeys` (a value of `None` means "all keys").  Major and minor fields are those of `M`, with entries outside the submatrix filtered out.  Since the submatrix is itself an oracle, it can be passed to any decomposition function; e.g. `decomp_row(&Submatrix::new(&matrix, None, Some(IndexDomain::Hashset(min_to_reduce))), &mut maj_to_reduce)` is how [`decomp_row_use_pairs`](crate::decomp_row_use_pairs::decomp_row_use_pairs) is implemented.
+///
+/// The `is_pivot` and `is_apparent` shortcuts of the inner oracle are not forwarded, since they describe the full matrix and may fail for a submatrix.
+pub struct Submatrix<'a, MajKey, MinKey, SnzVal>{
+    pub oracle: &'a dyn SmOracle<MajKey, MinKey, SnzVal>,
+    pub majkeys: Option<IndexDomain<MajKey>>,
+    pub minkeys: Option<IndexDomain<MinKey>>
+}
 
-/// This function should return an error if IndexedSmo.indexmaj_dom does not provide a finite set (if IndexedSmo.indexmaj_domain = Everything).
-fn transpose <'a, MajKeyOuter, MajKeyInner, MinKeyOuter, MinKeyInner, SnzVal: Clone, IndexType>
-    (S: IndexedSmo<'a, MajKeyOuter, MajKeyInner, MinKeyOuter, MinKeyInner, SnzVal, IndexType>) {
+/// Methods of Submatrix
+impl<'a, MajKey, MinKey, SnzVal> Submatrix<'a, MajKey, MinKey, SnzVal> {
+    /// Restrict `oracle` to the given major and minor keys; use `None` to keep all keys.
+    pub fn new(
+        oracle:     &'a dyn SmOracle<MajKey, MinKey, SnzVal>,
+        majkeys:    Option<IndexDomain<MajKey>>,
+        minkeys:    Option<IndexDomain<MinKey>>
+    ) -> Submatrix<'a, MajKey, MinKey, SnzVal> {
+        Submatrix{ oracle, majkeys, minkeys }
+    }
+}
+
+/// Methods of Submatrix
+impl<'a, MajKey, MinKey, SnzVal> Submatrix<'a, MajKey, MinKey, SnzVal> where
+MajKey: PartialEq + Eq + Hash + Clone + PartialOrd,
+MinKey: PartialEq + Eq + Hash + Clone + PartialOrd + Debug,
+SnzVal: Clone + PartialEq + Debug
+{
+
+    /// Export the submatrix to a CSM, whose major fields appear in the order given by `majkeys.keys()`.  Returns `None` if the major keys cannot be enumerated (see [`IndexDomain::keys`](IndexDomain::keys)).
+    pub fn into_csm( &self ) -> Option<CSM<MinKey, SnzVal>> {
+        let mut csm = CSM::new(self.oracle.maj_dim(), self.oracle.ring().clone());
+        for majkey in self.majkeys.as_ref()?.keys()? {
+            for (minkey, val) in self.maj_itr(&majkey) {
+                csm.push_snzval(minkey, val);
+            }
+            csm.majptr.push(csm.minind.len());
+            csm.nummaj += 1;
+        }
+        Some(csm)
+    }
+}
+
+// See the SmOracle trait for a description of each method
+impl<'a, MajKey, MinKey, SnzVal> SmOracle<MajKey, MinKey, SnzVal> for Submatrix<'a, MajKey, MinKey, SnzVal> where
+MajKey: PartialEq + Eq + Hash + Clone + PartialOrd,
+MinKey: PartialEq + Eq + Hash + Clone + PartialOrd,
+SnzVal: Clone
+{
+    fn ring( &self ) -> &RingMetadata<SnzVal> { self.oracle.ring() }
 
-    }// -> IndexedSmo<MinKey, usize, MajKey, MajKey, Element, >;
-    // IndexedSmo<MajKeyOuter, MajKeyInner, MinKeyOuter, MinKeyInner, SnzVal>
+    fn maj_dim( &self ) -> MajorDimension { self.oracle.maj_dim() }
+
+    fn maj_itr( &self, majkey: &MajKey ) -> Box<dyn Iterator<Item=(MinKey, SnzVal)> + '_> {
+        if let Some(domain) = &self.majkeys {
+            if !domain.contains(majkey) { return Box::new(std::iter::empty()); }
+        }
+        match &self.minkeys {
+            Some(domain) => Box::new(self.oracle.maj_itr(majkey).filter(move |(key, _)| domain.contains(key))),
+            None => self.oracle.maj_itr(majkey)
+        }
+    }
+
+    fn min_itr( &self, minkey: &MinKey ) -> Box<dyn Iterator<Item=(MajKey, SnzVal)> + '_> {
+        if let Some(domain) = &self.minkeys {
+            if !domain.contains(minkey) { return Box::new(std::iter::empty()); }
+        }
+        match &self.majkeys {
+            Some(domain) => Box::new(self.oracle.min_itr(minkey).filter(move |(key, _)| domain.contains(key))),
+            None => self.oracle.min_itr(minkey)
+        }
+    }
+
+    fn maj_itr_sorted( &self ) -> bool { self.oracle.maj_itr_sorted() }
+
+    fn min_itr_sorted( &self ) -> bool { self.oracle.min_itr_sorted() }
+
+    fn finiteminors( &self ) -> Option<bool> { self.oracle.finiteminors() }
+
+    fn finitemajors( &self ) -> Option<bool> { self.oracle.finitemajors() }
+}
 
 
 // SCALING FOR SMORacles
@@ -784,13 +1891,272 @@ fn transpose <'a, MajKeyOuter, MajKeyInner, MinKeyOuter, MinKeyInner, SnzVal: Cl
 // EXAMPLES
 // --------
 
-/// This function returns a lazy `product` SmOracle, but the function doesn't have much work to do.  It just places `v` into a SmOracle_Product struct.
-/// **All SmOracles should have the same major dimension.**
-fn lazy_prod<SmOracleTypes>( sequence: Vec<Option<SmOracleTypes>> ){}// -> Oracle_Product;
+/// Iterator that merges several sparse vectors (each scaled by a coefficient) into a single sparse vector.
+///
+/// Entries are returned in ascending order of keys.  Entries with equal keys are added together, and entries that sum to zero are dropped.  Vectors passed to [`push_sorted`](MergeItr::push_sorted) must already be in ascending order, and are read lazily, one entry at a time; vectors passed to [`push`](MergeItr::push) may be in any order, and are collected and sorted first.
+pub struct MergeItr<'a, Key, SnzVal: Clone>{
+    ringmetadata: RingMetadata<SnzVal>,                     // coefficient ring
+    summands: Vec<Summand<'a, Key, SnzVal>>,               // (sorted summand, scale factor)
+    heads: Vec<Option<(Key, SnzVal)>>,                      // the next entry of each summand
+    heap: BinaryHeap<Reverse<(Key, usize)>>                 // (key of head, summand number)
+}
+
+/// A summand of a [`MergeItr`](MergeItr): an iterator that runs over a sorted sparse vector, and the coefficient it is scaled by
+type Summand<'a, Key, SnzVal> = (Box<dyn Iterator<Item=(Key, SnzVal)> + 'a>, SnzVal);
+
+/// Methods of MergeItr
+impl<'a, Key, SnzVal> MergeItr<'a, Key, SnzVal> where
+Key: Ord + Clone + 'a,
+SnzVal: RingElement + 'a
+{
+    /// Construct an empty merge (whose sum is zero)
+    pub fn new( ringmetadata: RingMetadata<SnzVal> ) -> MergeItr<'a, Key, SnzVal> {
+        MergeItr{
+            ringmetadata,
+            summands: Vec::new(),
+            heads: Vec::new(),
+            heap: BinaryHeap::new()
+        }
+    }
+
+    /// Add `scale` times `vector` to the sum; the entries of `vector` may appear in any order
+    pub fn push<I: Iterator<Item=(Key, SnzVal)>>( &mut self, vector: I, scale: SnzVal ) {
+        let mut vec: Vec<(Key, SnzVal)> = vector.collect();
+        vec.sort_by(|a, b| a.0.cmp(&b.0));
+        self.push_sorted(vec.into_iter(), scale);
+    }
+
+    /// Add `scale` times `vector` to the sum; the entries of `vector` must appear in ascending order of keys
+    pub fn push_sorted<I: Iterator<Item=(Key, SnzVal)> + 'a>( &mut self, vector: I, scale: SnzVal ) {
+        let summand_number = self.summands.len();
+        self.summands.push((Box::new(vector), scale));
+        self.heads.push(None);
+        self.advance(summand_number);
+    }
+
+    /// Add `scale` times `vector` to the sum, sorting `vector` first unless `sorted` is true
+    fn push_field<I: Iterator<Item=(Key, SnzVal)> + 'a>( &mut self, vector: I, scale: SnzVal, sorted: bool ) {
+        if sorted { self.push_sorted(vector, scale) } else { self.push(vector, scale) }
+    }
+
+    /// Move the head of summand `summand_number` to its next entry
+    fn advance( &mut self, summand_number: usize ) {
+        let (itr, scale) = &mut self.summands[summand_number];
+        match itr.next() {
+            Some((key, val)) => {
+                self.heap.push(Reverse((key.clone(), summand_number)));
+                self.heads[summand_number] = Some((key, self.ringmetadata.multiply(scale, &val)));
+            }
+            None => { self.heads[summand_number] = None; }
+        }
+    }
+}
+
+impl<'a, Key, SnzVal> Iterator for MergeItr<'a, Key, SnzVal> where
+Key: Ord + Clone + 'a,
+SnzVal: RingElement + 'a
+{
+    type Item = (Key, SnzVal);
+
+    fn next( &mut self ) -> Option<Self::Item> {
+        while let Some(Reverse((key, summand_number))) = self.heap.pop() {
+            let mut value = self.heads[summand_number].take().unwrap().1;
+            self.advance(summand_number);
+            while let Some(Reverse((nextkey, _))) = self.heap.peek() {
+                if *nextkey != key { break; }
+                let Reverse((_, nextnumber)) = self.heap.pop().unwrap();
+                value = self.ringmetadata.add(&value, &self.heads[nextnumber].take().unwrap().1);
+                self.advance(nextnumber);
+            }
+            let value = self.ringmetadata.simplify(&value);
+            if !self.ringmetadata.is_0(&value) { return Some((key, value)); }
+        }
+        None
+    }
+}
+
+/// A lazy product of two sparse matrix oracles.
+///
+/// Major field `majkey` of the product is computed on demand by taking major field `majkey` of `first`, and replacing each entry `(k, v)` with `v` times major field `k` of `second`.  If both oracles are row-major then this is the matrix product `first * second`; if both are column-major then it is the matrix product `second * first`.  Minor fields are computed in the analogous fashion.  Fields are returned in ascending order of keys, with no zero entries.
+///
+/// The fields of the factors are merged lazily when the factors report that they are sorted (see [`maj_itr_sorted`](SmOracle::maj_itr_sorted)), and sorted first otherwise.  A product is itself a sorted oracle, so products of three or more factors can be formed by nesting, e.g. `lazy_prod(lazy_prod(&a, &b), &c)`, or with the [`lazy_prod!`](crate::lazy_prod!) macro.  The factors can be oracles or references to oracles; the key types are recorded as type parameters, with `MidKey` the type of the keys that `first` and `second` have in common.
+pub struct OracleProduct<MajKey, MidKey, MinKey, SnzVal, First, Second>{
+    first: First,
+    second: Second,
+    phantom: PhantomData<(MajKey, MidKey, MinKey, SnzVal)>
+}
+
+// See the SmOracle trait for a description of each method
+impl<MajKey, MidKey, MinKey, SnzVal, First, Second> SmOracle<MajKey, MinKey, SnzVal> for OracleProduct<MajKey, MidKey, MinKey, SnzVal, First, Second> where
+MajKey: PartialEq + Eq + Hash + Clone + Ord,
+MidKey: PartialEq + Eq + Hash + Clone + Ord,
+MinKey: PartialEq + Eq + Hash + Clone + Ord,
+SnzVal: RingElement,
+First: SmOracle<MajKey, MidKey, SnzVal>,
+Second: SmOracle<MidKey, MinKey, SnzVal>
+{
+    fn ring( &self ) -> &RingMetadata<SnzVal> { self.first.ring() }
+
+    fn maj_dim( &self ) -> MajorDimension { self.first.maj_dim() }
+
+    fn maj_itr( &self, majkey: &MajKey ) -> Box<dyn Iterator<Item=(MinKey, SnzVal)> + '_> {
+        let sorted = self.second.maj_itr_sorted();
+        let mut merge = MergeItr::new(self.ring().clone());
+        for (midkey, val) in self.first.maj_itr(majkey) {
+            merge.push_field(self.second.maj_itr(&midkey), val, sorted);
+        }
+        Box::new(merge)
+    }
+
+    fn min_itr( &self, minkey: &MinKey ) -> Box<dyn Iterator<Item=(MajKey, SnzVal)> + '_> {
+        let sorted = self.first.min_itr_sorted();
+        let mut merge = MergeItr::new(self.ring().clone());
+        for (midkey, val) in self.second.min_itr(minkey) {
+            merge.push_field(self.first.min_itr(&midkey), val, sorted);
+        }
+        Box::new(merge)
+    }
+
+    fn maj_itr_sorted( &self ) -> bool { true }
+
+    fn min_itr_sorted( &self ) -> bool { true }
+
+    fn finiteminors( &self ) -> Option<bool> {
+        match (self.first.finiteminors(), self.second.finiteminors()) {
+            (Some(a), Some(b)) => Some(a && b),
+            _ => None
+        }
+    }
+
+    fn finitemajors( &self ) -> Option<bool> {
+        match (self.first.finitemajors(), self.second.finitemajors()) {
+            (Some(a), Some(b)) => Some(a && b),
+            _ => None
+        }
+    }
+}
+
+/// A lazy sum of sparse matrix oracles.
+///
+/// Each major (respectively, minor) field of the sum is obtained by merging the corresponding fields of the summands, lazily if the summands report that they are sorted.  Fields are returned in ascending order of keys, with no zero entries.
+pub struct OracleSum<'a, MajKey, MinKey, SnzVal>{
+    summands: Vec<&'a dyn SmOracle<MajKey, MinKey, SnzVal>>
+}
+
+// See the SmOracle trait for a description of each method
+impl<'a, MajKey, MinKey, SnzVal> SmOracle<MajKey, MinKey, SnzVal> for OracleSum<'a, MajKey, MinKey, SnzVal> where
+MajKey: PartialEq + Eq + Hash + Clone + Ord,
+MinKey: PartialEq + Eq + Hash + Clone + Ord,
+SnzVal: RingElement
+{
+    fn ring( &self ) -> &RingMetadata<SnzVal> { self.summands[0].ring() }
+
+    fn maj_dim( &self ) -> MajorDimension { self.summands[0].maj_dim() }
+
+    fn maj_itr( &self, majkey: &MajKey ) -> Box<dyn Iterator<Item=(MinKey, SnzVal)> + '_> {
+        let mut merge = MergeItr::new(self.ring().clone());
+        for summand in self.summands.iter() {
+            merge.push_field(summand.maj_itr(majkey), self.ring().identity_multiplicative.clone(), summand.maj_itr_sorted());
+        }
+        Box::new(merge)
+    }
+
+    fn min_itr( &self, minkey: &MinKey ) -> Box<dyn Iterator<Item=(MajKey, SnzVal)> + '_> {
+        let mut merge = MergeItr::new(self.ring().clone());
+        for summand in self.summands.iter() {
+            merge.push_field(summand.min_itr(minkey), self.ring().identity_multiplicative.clone(), summand.min_itr_sorted());
+        }
+        Box::new(merge)
+    }
+
+    fn maj_itr_sorted( &self ) -> bool { true }
+
+    fn min_itr_sorted( &self ) -> bool { true }
+
+    fn finiteminors( &self ) -> Option<bool> {
+        let mut finite = true;
+        for summand in self.summands.iter() { finite = finite && summand.finiteminors()?; }
+        Some(finite)
+    }
+
+    fn finitemajors( &self ) -> Option<bool> {
+        let mut finite = true;
+        for summand in self.summands.iter() { finite = finite && summand.finitemajors()?; }
+        Some(finite)
+    }
+}
+
+/// This function returns a lazy `product` SmOracle, but the function doesn't have much work to do.  It just places `first` and `second` into an [`OracleProduct`](OracleProduct) struct.
+/// **Both SmOracles should have the same major dimension.**  See [`OracleProduct`](OracleProduct) for the order of multiplication.
+///
+/// For three or more factors, nest the calls or use the [`lazy_prod!`](crate::lazy_prod!) macro.  For example, if `tinv`, `matrix`, `s` and `m` are the matrices *T<sup>-1</sup>*, *D*, *S* and *M* of a U-match factorization *TM = DS*, then the product *T<sup>-1</sup> D S* equals *M*:
+///
+/// ```
+/// use exhact::matrix::{RingSpec, RingMetadata, MajorDimension, SmOracle};
+/// use exhact::csm::CSM;
+/// use exhact::decomp_row::decomp_row;
+/// use exhact::umatch::UMatch;
+///
+/// // a 3x3 matrix with coefficients in the field of order 3
+/// let ringmetadata = RingMetadata{ ringspec: RingSpec::Modulus(3), identity_additive: 0, identity_multiplicative: 1 };
+/// let mut matrix = CSM::new(MajorDimension::Row, ringmetadata);
+/// matrix.append_maj(&mut vec![(0, 1), (1, 1)].into_iter().collect());
+/// matrix.append_maj(&mut vec![(1, 1), (2, 1)].into_iter().collect());
+/// matrix.append_maj(&mut vec![(0, 1), (1, 2), (2, 1)].into_iter().collect());
+///
+/// let mut maj_to_reduce = vec![0, 1, 2];
+/// let (rowoper, indexing) = decomp_row(&matrix, &mut maj_to_reduce).unwrap();
+/// let umatch = UMatch::new(&matrix, &rowoper, &indexing);
+///
+/// // T^{-1} D S, computed lazily
+/// let product = exhact::lazy_prod!(umatch.cob_row(true), &matrix, umatch.cob_col(false));
+/// let matching = umatch.matching();
+/// for key in 0..3 {
+///     assert_eq!(product.maj_itr(&key).collect::<Vec<_>>(), matching.maj_itr(&key).collect::<Vec<_>>());
+///     assert_eq!(product.min_itr(&key).collect::<Vec<_>>(), matching.min_itr(&key).collect::<Vec<_>>());
+/// }
+/// ```
+pub fn lazy_prod<MajKey, MidKey, MinKey, SnzVal, First, Second>(
+    first:  First,
+    second: Second
+) -> OracleProduct<MajKey, MidKey, MinKey, SnzVal, First, Second> where
+MajKey: PartialEq + Eq + Hash + Clone,
+MidKey: PartialEq + Eq + Hash + Clone,
+MinKey: PartialEq + Eq + Hash + Clone,
+SnzVal: Clone,
+First: SmOracle<MajKey, MidKey, SnzVal>,
+Second: SmOracle<MidKey, MinKey, SnzVal>
+{
+    assert!(first.maj_dim() == second.maj_dim(), "lazy_prod: both factors should have the same major dimension");
+    OracleProduct{ first, second, phantom: PhantomData }
+}
+
+/// A lazy product of two or more sparse matrix oracles: `lazy_prod!(a, b, c)` is shorthand for `lazy_prod(lazy_prod(a, b), c)`.  See [`lazy_prod`](crate::matrix::lazy_prod) for an example.
+#[macro_export]
+macro_rules! lazy_prod {
+    ( $first:expr, $second:expr ) => {
+        $crate::matrix::lazy_prod($first, $second)
+    };
+    ( $first:expr, $second:expr, $( $rest:expr ),+ ) => {
+        $crate::lazy_prod!($crate::matrix::lazy_prod($first, $second), $( $rest ),+)
+    };
+}
 
-/// This function returns a lazy `sum` SmOracle, but the function doesn't have much work to do.  It just places `v` into a SmOracle_Sum struct.
+/// This function returns a lazy `sum` SmOracle, but the function doesn't have much work to do.  It just places `sequence` into an [`OracleSum`](OracleSum) struct.
 /// **All SmOracles should have the same major dimension.**
-fn lazy_sum<SmOracleTypes> ( sequence: Vec<Option<SmOracleTypes>> ){}// -> Oracle_Sum; //  returns a lazy 'sum' SmOracle
+pub fn lazy_sum<'a, MajKey, MinKey, SnzVal>(
+    sequence: Vec<&'a dyn SmOracle<MajKey, MinKey, SnzVal>>
+) -> OracleSum<'a, MajKey, MinKey, SnzVal> where
+MajKey: PartialEq + Eq + Hash + Clone,
+MinKey: PartialEq + Eq + Hash + Clone,
+SnzVal: Clone
+{
+    assert!(!sequence.is_empty(), "lazy_sum: there should be at least one summand");
+    for summand in sequence.iter() {
+        assert!(summand.maj_dim() == sequence[0].maj_dim(), "lazy_sum: all summands should have the same major dimension");
+    }
+    OracleSum{ summands: sequence }
+}
 
 // NOTES
 // -----
@@ -804,39 +2170,103 @@ fn lazy_sum<SmOracleTypes> ( sequence: Vec<Option<SmOracleTypes>> ){}// -> Oracl
 
 // There are essentially three different sparse vector formats compatible with sparse matrix / vector multiplication, and all three can be converted to iterators at very low cost.  Therefore I'm proposing that we format the `vector` argument as an iterator.
 
-fn prod_major<T, MajKey, MinKey, SnzVal>(matrix: Box<dyn SmOracle<MajKey, MinKey, SnzVal>>, vector: T, majdim: MajorDimension)-> SparseVector<MinKey, SnzVal>
+/// Multiply a sparse vector with the major fields of a sparse matrix oracle: returns the sum of `val * matrix.maj_itr(key)` over all entries `(key, val)` of `vector`.
+///
+/// If the matrix is row-major then this is the vector-matrix product `vector * matrix`; if it is column-major then this is the matrix-vector product `matrix * vector`.
+///
+/// ```
+/// use exhact::matrix::{RingSpec, RingMetadata, MajorDimension, Transposed, prod_major, prod_minor};
+/// use exhact::csm::CSM;
+///
+/// // a row-major 2x2 matrix over the field of order 5
+/// //
+/// // [ 1 2 ]
+/// // [ 4 3 ]
+/// let ringmetadata = RingMetadata{ ringspec: RingSpec::Modulus(5), identity_additive: 0i16, identity_multiplicative: 1i16 };
+/// let mut matrix = CSM::new(MajorDimension::Row, ringmetadata);
+/// matrix.append_maj(&mut vec![(0, 1), (1, 2)].into_iter().collect());
+/// matrix.append_maj(&mut vec![(0, 4), (1, 3)].into_iter().collect());
+/// let vector = vec![(0, 1), (1, 1)];
+///
+/// // [1 1] * matrix = [5 5] = 0, so every entry cancels
+/// let product = prod_major(&matrix, vector.clone().into_iter());
+/// assert!(product.ind.is_empty());
+///
+/// // matrix * [1 1]^T = [3 7]^T = [3 2]^T
+/// let product = prod_minor(&matrix, vector.clone().into_iter());
+/// assert_eq!(product.ind, vec![0, 1]);
+/// assert_eq!(product.snz, vec![3, 2]);
+///
+/// // the transpose is a column-major view of the same matrix, so the roles of the two functions swap
+/// let columns = Transposed::new(&matrix);
+/// assert!(prod_minor(&columns, vector.clone().into_iter()).ind.is_empty());
+/// assert_eq!(prod_major(&columns, vector.into_iter()).snz, vec![3, 2]);
+/// ```
+pub fn prod_major<T, MajKey, MinKey, SnzVal, Matrix>( matrix: &Matrix, vector: T ) -> SparseVector<MinKey, SnzVal>
 where
     T: Iterator<Item=(MajKey, SnzVal)>,
+    MajKey: PartialEq + Eq + Hash + Clone,
+    MinKey: Ord + Eq + Hash + Clone,
+    SnzVal: RingElement,
+    Matrix: SmOracle<MajKey, MinKey, SnzVal> + ?Sized
 {
-    SparseVector {
-        ind: Vec::new(),
-        snz: Vec::new()
+    let mut merge = MergeItr::new(matrix.ring().clone());
+    let sorted = matrix.maj_itr_sorted();
+    for (key, val) in vector {
+        merge.push_field(matrix.maj_itr(&key), val, sorted);
     }
-}// -> SparseVector // the major dimension decides whether we multiply on the left or the right.
+    SparseVector::from_itr(matrix.ring(), merge)
+}
 
-fn prod_minor<T, MajKey, MinKey, SnzVal>(matrix: Box<dyn SmOracle<MajKey, MinKey, SnzVal>>, vector: Vec<T>, majdim: MajorDimension) -> SparseVector<MajKey, SnzVal>
+/// Multiply a sparse vector with the minor fields of a sparse matrix oracle: returns the sum of `val * matrix.min_itr(key)` over all entries `(key, val)` of `vector`.
+///
+/// If the matrix is row-major then this is the matrix-vector product `matrix * vector`; if it is column-major then this is the vector-matrix product `vector * matrix`.
+pub fn prod_minor<T, MajKey, MinKey, SnzVal, Matrix>( matrix: &Matrix, vector: T ) -> SparseVector<MajKey, SnzVal>
 where
-    T: Iterator<Item=(MajKey, SnzVal)>,
+    T: Iterator<Item=(MinKey, SnzVal)>,
+    MajKey: Ord + Eq + Hash + Clone,
+    MinKey: PartialEq + Eq + Hash + Clone,
+    SnzVal: RingElement,
+    Matrix: SmOracle<MajKey, MinKey, SnzVal> + ?Sized
 {
-    SparseVector {
-        ind: Vec::new(),
-        snz: Vec::new()
+    let mut merge = MergeItr::new(matrix.ring().clone());
+    let sorted = matrix.min_itr_sorted();
+    for (key, val) in vector {
+        merge.push_field(matrix.min_itr(&key), val, sorted);
     }
-}// -> SparseVector // the major dimension decides whether we multiply on the left or the right.  Note that we have to use a IndexedSmo in this conext, since we need to know the IndexDomain of the major dimension.
+    SparseVector::from_itr(matrix.ring(), merge)
+}
 
 
 // -----------------------------------------------------------------------------------------------
 // VECTOR VECTOR OPERATIONS
 // -----------------------------------------------------------------------------------------------
 
-fn sum_vector<T, MajKey, SnzVal>( vector1: T, vector2: T ) -> SparseVector<MajKey, SnzVal>
+/// Returns the sum of two sparse vectors, with entries sorted in ascending order and zeros removed.  The inputs need not be sorted.
+///
+/// ```
+/// use exhact::matrix::{RingSpec, RingMetadata, sum_vector};
+///
+/// let ringmetadata = RingMetadata{ ringspec: RingSpec::Modulus(5), identity_additive: 0i16, identity_multiplicative: 1i16 };
+/// let vector1 = vec![(2, 1), (0, 3)];
+/// let vector2 = vec![(0, 2), (1, 1)];
+///
+/// // the entries with key 0 sum to 5 = 0, and are dropped
+/// let sum = sum_vector(&ringmetadata, vector1.into_iter(), vector2.into_iter());
+/// assert_eq!(sum.ind, vec![1, 2]);
+/// assert_eq!(sum.snz, vec![1, 1]);
+/// ```
+pub fn sum_vector<T, U, MajKey, SnzVal>( ringmetadata: &RingMetadata<SnzVal>, vector1: T, vector2: U ) -> SparseVector<MajKey, SnzVal>
 where
     T: Iterator<Item=(MajKey, SnzVal)>,
+    U: Iterator<Item=(MajKey, SnzVal)>,
+    MajKey: Ord + Eq + Hash + Clone,
+    SnzVal: RingElement
 {
-    SparseVector {
-        ind: Vec::new(),
-        snz: Vec::new()
-    }
+    let mut merge = MergeItr::new(ringmetadata.clone());
+    merge.push(vector1, ringmetadata.identity_multiplicative.clone());
+    merge.push(vector2, ringmetadata.identity_multiplicative.clone());
+    SparseVector::from_itr(ringmetadata, merge)
 }
 
 // -----------------------------------------------------------------------------------------------
@@ -0,0 +1,346 @@
+/*!
+
+Matrix Market (`.mtx`) import and export
+
+# Matrix Market files
+
+The [Matrix Market exchange format](https://math.nist.gov/MatrixMarket/formats.html) is a plain text
+format for sparse matrices that MATLAB, Julia, SciPy and most other numerical environments can read.
+This module writes matrices in *coordinate* form: a header, a line `nrows ncols nnz`, and one line
+`row col value` per structural nonzero entry, with 1-based indices.
+
+* [`write_csm_mtx`](write_csm_mtx) and [`read_csm_mtx`](read_csm_mtx) save and load a [`CSM`](crate::csm::CSM)
+  (for example the [`as_csm`](crate::csm::DualCsm::as_csm) of a row operation matrix in [`FactoredComplexBlockCsm::dim_rowoper`](crate::chx::FactoredComplexBlockCsm)).
+* [`write_smoracle_mtx`](write_smoracle_mtx) saves any [`SmOracle`](crate::matrix::SmOracle), given the lists of its major and minor keys.
+* [`write_boundary_mtx`](write_boundary_mtx) saves a boundary matrix of any [`ChainComplex`](crate::chx::ChainComplex),
+  together with a sidecar file that maps row and column numbers back to simplices, cubes, etc.
+
+Coefficients in `Integer` and `Modulus` rings are written with field type `integer`, and coefficients in
+`Float` rings with field type `real`.  Coefficients are simplified before they are written, so entries of a
+`Modulus(n)` matrix are always written in `0..n`.  Matrix Market has no field type for exact rationals, so
+rational matrices cannot be exported.
+
+```
+use exhact::matrix::{RingSpec, RingMetadata, MajorDimension, SmOracle};
+use exhact::csm::CSM;
+use exhact::mtx::{write_csm_mtx, read_csm_mtx};
+
+let ringmetadata = RingMetadata{ ringspec: RingSpec::Modulus(3), identity_additive: 0, identity_multiplicative: 1 };
+let mut matrix: CSM<usize, i64> = CSM::new(MajorDimension::Row, ringmetadata.clone());
+matrix.append_maj(&mut vec![(0, 1), (2, 2)].into_iter().collect());
+matrix.append_maj(&mut vec![(1, 2)].into_iter().collect());
+
+let path = std::env::temp_dir().join("exhact_mtx_doctest.mtx");
+write_csm_mtx(&path, &matrix, 3).unwrap();
+let (copy, nummin) = read_csm_mtx::<i64, _>(&path, MajorDimension::Row, ringmetadata).unwrap();
+assert_eq!(nummin, 3);
+assert_eq!(copy.entry(&0, &2), Some(2));
+assert_eq!(copy.entry(&1, &1), Some(2));
+```
+
+*/
+
+use std::collections::HashMap;
+use std::fmt::{Debug, Display};
+use std::fs::File;
+use std::hash::Hash;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::chx::{ChainComplex, ChxTransformKind};
+use crate::csm::CSM;
+use crate::matrix::{SmOracle, RingElement, RingMetadata, RingSpec, MajorDimension, Semiring, Ring};
+
+/// The Matrix Market field type used to store coefficients of a given ring
+fn field_type(ringspec: &RingSpec) -> io::Result<&'static str> {
+    match ringspec {
+        RingSpec::Integer | RingSpec::Modulus(_) => Ok("integer"),
+        RingSpec::Float(_) => Ok("real"),
+        RingSpec::Rational => Err(io::Error::new(io::ErrorKind::InvalidInput, "Matrix Market has no field type for rational coefficients")),
+    }
+}
+
+/// An error for a malformed Matrix Market file
+fn invalid_data(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Write the header and the entries `(row, col, value)` (0-based) of a matrix in coordinate form
+fn write_coordinate<SnzVal: Display>(
+    path:       &Path,
+    field:      &str,
+    shape:      (usize, usize),
+    entries:    &[(usize, usize, SnzVal)],
+    comments:   &[String]
+) -> io::Result<()> {
+    let mut file = BufWriter::new(File::create(path)?);
+    writeln!(file, "%%MatrixMarket matrix coordinate {} general", field)?;
+    for comment in comments.iter() {
+        writeln!(file, "% {}", comment)?;
+    }
+    writeln!(file, "{} {} {}", shape.0, shape.1, entries.len())?;
+    for (row, col, val) in entries.iter() {
+        writeln!(file, "{} {} {}", row + 1, col + 1, val)?;
+    }
+    file.flush()
+}
+
+/// Write a CSM to a Matrix Market file, in coordinate form
+///
+/// Row-major matrices are written as they are; a column-major matrix with `nummaj` columns is written as an `nummin x nummaj` matrix.
+///
+/// # Parameters
+/// - `path`: The file to write
+/// - `matrix`: The matrix to write
+/// - `nummin`: Number of minor indices (e.g. the number of columns of a row-major CSM)
+///
+/// # Errors
+/// An error of kind `InvalidData` if the matrix has an entry with minor index `nummin` or larger.
+pub fn write_csm_mtx<SnzVal, P>(
+    path:       P,
+    matrix:     &CSM<usize, SnzVal>,
+    nummin:     usize
+) -> io::Result<()> where
+SnzVal: RingElement + Debug + Display,
+P: AsRef<Path>
+{
+    let maj_keys: Vec<usize> = (0..matrix.nummaj).collect();
+    let min_keys: Vec<usize> = (0..nummin).collect();
+    write_smoracle_mtx(path, matrix, &maj_keys, &min_keys)
+}
+
+/// Write a sparse matrix oracle to a Matrix Market file, in coordinate form
+///
+/// The matrix written has one major index for each key in `maj_keys` and one minor index for each key in `min_keys`, in the
+/// given order; as in [`write_csm_mtx`](write_csm_mtx), the major indices are rows if `matrix.maj_dim()` is `Row` and columns otherwise.
+/// Explicit zeros are skipped.
+///
+/// # Parameters
+/// - `path`: The file to write
+/// - `matrix`: The matrix to write
+/// - `maj_keys`: The major keys of the matrix, in the order in which they should be numbered
+/// - `min_keys`: The minor keys of the matrix, in the order in which they should be numbered
+///
+/// # Errors
+/// An error of kind `InvalidData` if a major vector has a nonzero entry whose minor key is not in `min_keys`.
+///
+/// ```
+/// use exhact::matrix::{RingSpec, RingMetadata, MajorDimension};
+/// use exhact::csm::CSM;
+/// use exhact::mtx::{write_smoracle_mtx, read_csm_mtx};
+///
+/// let ringmetadata = RingMetadata{ ringspec: RingSpec::Modulus(3), identity_additive: 0, identity_multiplicative: 1 };
+/// let mut matrix: CSM<usize, i64> = CSM::new(MajorDimension::Row, ringmetadata.clone());
+/// matrix.append_maj(&mut vec![(0, 1), (2, -1)].into_iter().collect());
+/// matrix.append_maj(&mut vec![(1, 1)].into_iter().collect());
+///
+/// // write the rows in reverse order, and only the first two columns
+/// let path = std::env::temp_dir().join("exhact_smoracle_mtx_doctest.mtx");
+/// assert!(write_smoracle_mtx(&path, &matrix, &[1, 0], &[0, 1]).is_err());
+/// write_smoracle_mtx(&path, &matrix, &[1, 0], &[0, 1, 2]).unwrap();
+/// let text = std::fs::read_to_string(&path).unwrap();
+/// assert_eq!(text.lines().skip(1).collect::<Vec<_>>(), vec!["2 3 3", "1 2 1", "2 1 1", "2 3 2"]);
+/// ```
+pub fn write_smoracle_mtx<MajKey, MinKey, SnzVal, Matrix, P>(
+    path:       P,
+    matrix:     &Matrix,
+    maj_keys:   &[MajKey],
+    min_keys:   &[MinKey]
+) -> io::Result<()> where
+MajKey: PartialEq + Eq + Hash + Clone,
+MinKey: PartialEq + Eq + Hash + Clone + Debug,
+SnzVal: RingElement + Display,
+Matrix: SmOracle<MajKey, MinKey, SnzVal>,
+P: AsRef<Path>
+{
+    write_smoracle_coordinate(path.as_ref(), matrix, matrix.maj_dim(), maj_keys, min_keys, &[])
+}
+
+/// Write the entries of a sparse matrix oracle, indexed by the positions of its keys in `maj_keys` and `min_keys`; the major indices are rows if `majdim` is `Row`
+fn write_smoracle_coordinate<MajKey, MinKey, SnzVal, Matrix>(
+    path:       &Path,
+    matrix:     &Matrix,
+    majdim:     MajorDimension,
+    maj_keys:   &[MajKey],
+    min_keys:   &[MinKey],
+    comments:   &[String]
+) -> io::Result<()> where
+MajKey: PartialEq + Eq + Hash + Clone,
+MinKey: PartialEq + Eq + Hash + Clone + Debug,
+SnzVal: RingElement + Display,
+Matrix: SmOracle<MajKey, MinKey, SnzVal>
+{
+    let ring = matrix.ring();
+    let field = field_type(&ring.ringspec)?;
+    let min_index: HashMap<&MinKey, usize> = min_keys.iter().enumerate().map(|(ii, key)| (key, ii)).collect();
+
+    let mut entries = Vec::new();
+    for (majind, majkey) in maj_keys.iter().enumerate() {
+        let mut field_entries = Vec::new();
+        for (minkey, val) in matrix.maj_itr(majkey) {
+            let val = ring.simplify(&val);
+            if ring.is_0(&val) { continue; }
+            let minind = *min_index.get(&minkey).ok_or_else(|| invalid_data(format!("the matrix has a nonzero entry with minor key {:?}, which is not in the list of minor keys", minkey)))?;
+            field_entries.push((minind, val));
+        }
+        field_entries.sort_by_key(|(minind, _)| *minind);
+        entries.extend(field_entries.into_iter().map(|(minind, val)| match majdim {
+            MajorDimension::Row => (majind, minind, val),
+            MajorDimension::Col => (minind, majind, val),
+        }));
+    }
+    let shape = match majdim {
+        MajorDimension::Row => (maj_keys.len(), min_keys.len()),
+        MajorDimension::Col => (min_keys.len(), maj_keys.len()),
+    };
+    write_coordinate(path, field, shape, &entries, comments)
+}
+
+/// Read a CSM from a Matrix Market file in coordinate form
+///
+/// Files with `general`, `symmetric` and `skew-symmetric` symmetry, and with `integer`, `real` or `pattern` fields
+/// are supported (the entries of a `pattern` matrix are all equal to one).  Coefficients are parsed with `FromStr`,
+/// then simplified with `ringmetadata`; repeated entries are summed, and entries that become zero are dropped.
+///
+/// # Parameters
+/// - `path`: The file to read
+/// - `majdim`: The major dimension of the CSM to build
+/// - `ringmetadata`: Ring meta data of the coefficients
+///
+/// # Returns
+/// The matrix, and the number of its minor indices (e.g. the number of columns, if `majdim` is `Row`)
+pub fn read_csm_mtx<SnzVal, P>(
+    path:           P,
+    majdim:         MajorDimension,
+    ringmetadata:   RingMetadata<SnzVal>
+) -> io::Result<(CSM<usize, SnzVal>, usize)> where
+SnzVal: RingElement + Debug + FromStr,
+P: AsRef<Path>
+{
+    let mut lines = BufReader::new(File::open(path)?).lines();
+
+    let header = lines.next().ok_or_else(|| invalid_data("the file is empty".to_string()))??;
+    let header: Vec<String> = header.split_whitespace().map(|word| word.to_lowercase()).collect();
+    if header.len() != 5 || header[0] != "%%matrixmarket" || header[1] != "matrix" || header[2] != "coordinate" {
+        return Err(invalid_data(format!("expected a Matrix Market header for a coordinate matrix, found {:?}", header)));
+    }
+    let pattern = header[3] == "pattern";
+    let symmetry = header[4].clone();
+    if !["general", "symmetric", "skew-symmetric"].contains(&symmetry.as_str()) {
+        return Err(invalid_data(format!("unsupported symmetry type {}", symmetry)));
+    }
+
+    let parse_index = |word: Option<&str>| -> io::Result<usize> {
+        word.and_then(|word| word.parse::<usize>().ok())
+            .ok_or_else(|| invalid_data(format!("could not parse an index from {:?}", word)))
+    };
+
+    // the size line is the first line that is neither blank nor a comment
+    let mut shape = None;
+    let mut fields: Vec<HashMap<usize, SnzVal>> = Vec::new();
+    for line in lines {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('%') { continue; }
+        let mut words = line.split_whitespace();
+
+        let (nrows, ncols) = match shape {
+            Some(shape) => shape,
+            None => {
+                let nrows = parse_index(words.next())?;
+                let ncols = parse_index(words.next())?;
+                shape = Some((nrows, ncols));
+                let nummaj = match majdim { MajorDimension::Row => nrows, MajorDimension::Col => ncols };
+                fields = (0..nummaj).map(|_| HashMap::new()).collect();
+                continue;
+            }
+        };
+
+        let row = parse_index(words.next())?;
+        let col = parse_index(words.next())?;
+        if row == 0 || row > nrows || col == 0 || col > ncols {
+            return Err(invalid_data(format!("entry ({}, {}) is out of bounds for a {} x {} matrix", row, col, nrows, ncols)));
+        }
+        let value = if pattern {
+            ringmetadata.one()
+        } else {
+            let word = words.next().ok_or_else(|| invalid_data(format!("missing value in line {:?}", line)))?;
+            word.parse::<SnzVal>().map_err(|_| invalid_data(format!("could not parse a coefficient from {:?}", word)))?
+        };
+
+        let mut entries = vec![(row - 1, col - 1, value.clone())];
+        if row != col && symmetry == "symmetric" {
+            entries.push((col - 1, row - 1, value));
+        } else if row != col && symmetry == "skew-symmetric" {
+            entries.push((col - 1, row - 1, ringmetadata.negate(&value)));
+        }
+        for (row, col, value) in entries {
+            let (majind, minind) = match majdim { MajorDimension::Row => (row, col), MajorDimension::Col => (col, row) };
+            let field = &mut fields[majind];
+            let sum = match field.remove(&minind) {
+                Some(previous) => ringmetadata.add(&previous, &value),
+                None => value,
+            };
+            field.insert(minind, sum);
+        }
+    }
+
+    let (nrows, ncols) = shape.ok_or_else(|| invalid_data("the file has no size line".to_string()))?;
+    let nummin = match majdim { MajorDimension::Row => ncols, MajorDimension::Col => nrows };
+    let mut matrix = CSM::new(majdim, ringmetadata);
+    for mut field in fields {
+        field = field.into_iter()
+            .map(|(minind, val)| (minind, matrix.ringmetadata.simplify(&val)))
+            .filter(|(_, val)| !matrix.ringmetadata.is_0(val))
+            .collect();
+        matrix.append_maj(&mut field);
+    }
+    Ok((matrix, nummin))
+}
+
+/// Write the boundary matrix of a chain complex in a given dimension to a Matrix Market file, with a sidecar file of keys
+///
+/// Rows are indexed by `original_complex.keys_ordered(dim - 1)` and columns by `original_complex.keys_ordered(dim)`, so the
+/// matrix is sorted by filtration.  Two files are written:
+/// * `path`: the boundary matrix, in coordinate form
+/// * `path` followed by `.keys`: one tab-separated line `row <i> <filtration> <key>` or `col <j> <filtration> <key>` per index,
+///   where `i` and `j` are the 1-based row and column numbers used in the matrix file, and keys and filtrations are written in `Debug` format
+///
+/// The chain complex must be able to build boundaries in degree `dim`.
+///
+/// # Errors
+/// An error of kind `InvalidData` if the boundary of a key in `keys_ordered(dim)` has a face that is not in `keys_ordered(dim - 1)`.
+///
+/// # Parameters
+/// - `original_complex`: The chain complex
+/// - `dim`: The dimension of the columns (the boundary maps dimension `dim` to dimension `dim - 1`); must be positive
+/// - `path`: The matrix file to write
+pub fn write_boundary_mtx<MatrixIndexKey, SnzVal, Filtration, OriginalChx, P>(
+    original_complex:   &OriginalChx,
+    dim:                usize,
+    path:               P
+) -> io::Result<()> where
+MatrixIndexKey: PartialEq + Eq + Hash + Clone + Debug,
+SnzVal: RingElement + Display,
+Filtration: Debug,
+OriginalChx: ChainComplex<MatrixIndexKey, SnzVal, Filtration>,
+P: AsRef<Path>
+{
+    assert!(dim > 0, "a boundary matrix must have columns of positive dimension");
+    let matrix = original_complex.get_smoracle(MajorDimension::Col, ChxTransformKind::Boundary);
+    let row_keys = original_complex.keys_ordered(dim - 1);
+    let col_keys = original_complex.keys_ordered(dim);
+    let comments = vec![format!("boundary matrix in dimension {}; see the sidecar file for the keys of each row and column", dim)];
+    write_smoracle_coordinate(path.as_ref(), &matrix, MajorDimension::Col, &col_keys, &row_keys, &comments)?;
+
+    let mut sidecar_path = path.as_ref().as_os_str().to_owned();
+    sidecar_path.push(".keys");
+    let mut sidecar = BufWriter::new(File::create(sidecar_path)?);
+    for (label, keys) in [("row", &row_keys), ("col", &col_keys)].iter() {
+        for (ii, key) in keys.iter().enumerate() {
+            writeln!(sidecar, "{}\t{}\t{:?}\t{:?}", label, ii + 1, original_complex.key_2_filtration(key), key)?;
+        }
+    }
+    sidecar.flush()
+}
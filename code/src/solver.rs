@@ -6,7 +6,7 @@ Algebraic operations for sparse matrix oracles
 
 ```
 Example:
-     - perform triangular solve for x such that Ax = b
+     - perform triangular solve for x such that Ax = b (see triangular_solve for a working example)
 
 Example:
      - check the solution by confirming that Ax = b
@@ -16,86 +16,174 @@ Example:
 
 */
 
-use std::collections::BinaryHeap;
+use std::collections::{BinaryHeap, BTreeMap};
 use std::collections::HashMap;
-use std::ops::{Neg, AddAssign, Mul};
 use std::cmp::Reverse;
 use std::hash::Hash;
 use std::fmt::Debug;
 use crate::csm::CSM;
 use crate::decomp_row::update_heap_hash; // Haibin: maybe we should just put update_heap_hash() in this solver.rs file
-use crate::matrix::{SparseVector, InvMod, SmOracle, RingMetadata};
+use crate::matrix::{RingElement, Semiring, Ring, DivisionRing, SmOracle, RingMetadata, MajorDimension};
 use crate::chx::Indexing;
 
-/// Solve upper*xx = bb
+/// Which entries of a triangular matrix may be nonzero
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Triangle {
+    /// Entries on or above the diagonal (row key <= column key)
+    Upper,
+    /// Entries on or below the diagonal (row key >= column key)
+    Lower,
+}
+
+/// Which side of the matrix the unknown vector is on
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SolveSide {
+    /// Solve `x A = b` for a row vector `x`
+    Left,
+    /// Solve `A x = b` for a column vector `x`
+    Right,
+}
+
+/// The reason why [`triangular_solve`](triangular_solve) could not solve a system
+#[derive(Clone, Debug, PartialEq)]
+pub enum TriangularSolveError<Key> {
+    /// The diagonal entry in position `(key, key)` is zero (or missing), or is not invertible
+    SingularDiagonal{ key: Key },
+    /// The diagonal entry in position `(key, key)` is not one, but a unit diagonal was required
+    NotUnitDiagonal{ key: Key },
+    /// The field of `key` has a nonzero entry in position `offending_key`, on the wrong side of the diagonal
+    NotTriangular{ key: Key, offending_key: Key },
+}
+
+/// Solve a triangular system `x A = b` or `A x = b` for a sparse vector `x`
+///
+/// The matrix `A` can be any sparse matrix oracle whose rows and columns are indexed by the same ordered key type (for example a `CSM<usize, _>`, or one of the lazy change of basis oracles of a [`UMatch`](crate::umatch::UMatch)); it is triangular with respect to the order on keys.
+/// To solve `x A = b` we need rows of `A`, and to solve `A x = b` we need columns, so the system is fastest to solve when these are the major fields of the oracle.
+///
+/// Only the fields of `A` needed to compute `x` are visited, and they are checked as they are visited: an error is returned if one of them has a nonzero entry on the wrong side of the diagonal, or a diagonal entry that is not invertible (or not one, if `check_unit_diagonal` is true).
+///
+/// ```
+/// use exhact::matrix::{RingSpec, RingMetadata, MajorDimension, SmOracle};
+/// use exhact::csm::CSM;
+/// use exhact::decomp_row::decomp_row;
+/// use exhact::umatch::UMatch;
+/// use exhact::solver::{triangular_solve, Triangle, SolveSide};
+/// use std::collections::HashMap;
+///
+/// let ringmetadata = RingMetadata{ ringspec: RingSpec::Modulus(3), identity_additive: 0, identity_multiplicative: 1 };
+/// let mut matrix = CSM::new(MajorDimension::Row, ringmetadata);
+/// matrix.append_maj(&mut vec![(0, 1), (1, 1)].into_iter().collect());
+/// matrix.append_maj(&mut vec![(1, 1), (2, 1)].into_iter().collect());
+/// matrix.append_maj(&mut vec![(0, 1), (1, 2), (2, 1)].into_iter().collect());
+/// let (rowoper, indexing) = decomp_row(&matrix, &mut vec![0, 1, 2]).unwrap();
+/// let umatch = UMatch::new(&matrix, &rowoper, &indexing);
+///
+/// // solve against the (lazy) upper unitriangular matrix T, using either its rows or its columns
+/// let t = umatch.cob_row(false);
+/// let unit: HashMap<usize, i16> = vec![(0, 1)].into_iter().collect();
+/// for (side, field) in vec![ (SolveSide::Left, t.maj_hash(&0)), (SolveSide::Right, t.min_hash(&0)) ] {
+///     let solution = triangular_solve(&t, Triangle::Upper, side, true, &field).unwrap();
+///     assert_eq!(solution, unit);
+/// }
+/// ```
 ///
 /// # Parameters
-/// - `uppper`: an upper triangular matrix in CSM format with diagnal entries to be 1
-/// - `bb`: constant column sparse vector
+/// - `matrix`: a square sparse matrix oracle, triangular with respect to the order on keys
+/// - `triangle`: whether `matrix` is upper or lower triangular
+/// - `side`: whether to solve `x A = b` or `A x = b`
+/// - `check_unit_diagonal`: if true, require every diagonal entry that we visit to equal one
+/// - `bb`: the right hand side `b`, represented as a hash map
 /// # Returns
-/// xx the unknow sparse vector
-pub fn triangular_solver<SnzVal: Neg + Neg<Output=SnzVal> + AddAssign + Mul + Mul<Output=SnzVal> + Clone + InvMod + InvMod<Output = SnzVal> + PartialEq + Debug>(
-    upper:  &CSM<usize, SnzVal>,
-    bb:     &SparseVector<usize, SnzVal>
-) -> SparseVector<usize, SnzVal>{
-
-    let mut hash = HashMap::new();
-    let mut heap = BinaryHeap::new();
-    for ii in 0..bb.ind.len() {
-        hash.insert(bb.ind[ii].clone(), bb.snz[ii].clone());
-        heap.push(Reverse(bb.ind[ii].clone()));
-    }
+/// the solution `x`, represented as a hash map (with simplified, nonzero coefficients)
+pub fn triangular_solve<Key, SnzVal, Matrix>(
+    matrix:                 &Matrix,
+    triangle:               Triangle,
+    side:                   SolveSide,
+    check_unit_diagonal:    bool,
+    bb:                     &HashMap<Key, SnzVal>
+) -> Result<HashMap<Key, SnzVal>, TriangularSolveError<Key>> where
+Key: PartialEq + Eq + Hash + Clone + Ord,
+SnzVal: RingElement,
+Matrix: SmOracle<Key, Key, SnzVal>
+{
+    // the fields of the matrix that we combine: rows to solve x A = b, and columns to solve A x = b
+    let use_major_fields = match side {
+        SolveSide::Left => matrix.maj_dim() == MajorDimension::Row,
+        SolveSide::Right => matrix.maj_dim() == MajorDimension::Col
+    };
+    let field = |key: &Key| if use_major_fields { matrix.maj_hash(key) } else { matrix.min_hash(key) };
 
-    let mut xx: SparseVector<usize, SnzVal> = SparseVector::new();
-
-    while let Some(Reverse(index)) = heap.pop() {
-        if let Some(value) = hash.remove(&index) {
-            if !upper.ringmetadata.is_0(&value) {
-                xx.ind.push(index.clone());
-                xx.snz.push(value.clone());
-                let mut row = upper.maj_hash(&index);
-                row.remove(&index);
-                let scale = -value;
-                update_heap_hash(&upper.ringmetadata, &mut heap, &mut hash, &mut row, &scale);
-            }
+    // each field has nonzero entries only on one side of the diagonal; we solve for the
+    // coefficient of the key at the far end of that side first
+    let ascending = match (triangle, side) {
+        (Triangle::Upper, SolveSide::Left) | (Triangle::Lower, SolveSide::Right) => true,
+        (Triangle::Upper, SolveSide::Right) | (Triangle::Lower, SolveSide::Left) => false
+    };
+
+    let ring = matrix.ring();
+    let mut residual: BTreeMap<Key, SnzVal> = BTreeMap::new();
+    add_assign_btree(ring, &mut residual, bb.clone(), &ring.one());
+
+    let mut xx = HashMap::new();
+    loop {
+        let next = if ascending { residual.pop_first() } else { residual.pop_last() };
+        let (key, value) = match next { Some(x) => x, None => break };
+        let value = ring.simplify(&value);
+        if ring.is_0(&value) { continue; }
+
+        let mut thisfield = field(&key);
+        let diagonal = match thisfield.remove(&key) {
+            Some(val) if !ring.is_0(&val) => ring.simplify(&val),
+            _ => return Err(TriangularSolveError::SingularDiagonal{ key })
+        };
+        if check_unit_diagonal && diagonal != ring.one() {
+            return Err(TriangularSolveError::NotUnitDiagonal{ key });
         }
+        let inverse = match ring.inverse(&diagonal) {
+            Some(inverse) => inverse,
+            None => return Err(TriangularSolveError::SingularDiagonal{ key })
+        };
+        let offending_key = thisfield.iter()
+            .find(|(other, val)| (if ascending { *other < &key } else { *other > &key }) && !ring.is_0(val))
+            .map(|(other, _)| other.clone());
+        if let Some(offending_key) = offending_key {
+            return Err(TriangularSolveError::NotTriangular{ key, offending_key });
+        }
+
+        let coefficient = ring.simplify(&ring.multiply(&value, &inverse));
+        add_assign_btree(ring, &mut residual, thisfield, &ring.negate(&coefficient));
+        xx.insert(key, coefficient);
     }
-    return xx;
+    Ok(xx)
 }
 
-/// Solve upper*xx = bb, solution is indexed by new key.
+/// Add `scale` times `row` to a sparse vector stored in a BTreeMap, dropping entries that become zero
 ///
 /// # Parameters
-/// - `uppper`: a non-singular upper triangular matrix in CSM format
-/// - `bb`: constant column sparse vector
-/// - `majind_pivot_minind`: a vector indicates pivot entry locations
-/// # Returns
-/// xx the unknow sparse vector
-pub fn triangular_solver_version_2<SnzVal>(
-    upper:                  &CSM<usize, SnzVal>, // column major csm
-    majind_pivot_minind:    &Vec<usize>,
-    mut bb:                 &mut HashMap<usize, SnzVal>
-) -> HashMap<usize, SnzVal> where
-SnzVal: Neg + Neg<Output=SnzVal> + AddAssign + Mul + Mul<Output=SnzVal> + Clone + InvMod + InvMod<Output = SnzVal> + PartialEq + Debug
+/// -`ringmetadata`: ring data of entries
+/// -`target`: the sparse vector to be updated
+/// -`row`: a row represented as a hash map
+/// - `scale`: the scale value
+pub fn add_assign_btree<Key, SnzVal>(
+    ringmetadata:   &RingMetadata<SnzVal>,
+    target:         &mut BTreeMap<Key, SnzVal>,
+    row:            HashMap<Key, SnzVal>,
+    scale:          &SnzVal
+) where
+Key: Ord,
+SnzVal: RingElement
 {
-    let mut xx: HashMap<usize, SnzVal> = HashMap::new();
-    let mut ii = upper.nummaj;
-    while ii > 0 {
-        if let Some(value) = bb.remove(&majind_pivot_minind[ii-1]) {
-            let mut hash = upper.maj_hash(&(ii-1));
-            if let Some(dominator) = hash.remove(&majind_pivot_minind[ii-1]) {
-                if let Some(inverse) = upper.ring().inverse(&dominator){
-                    let mut scale = value*inverse;
-                    scale = upper.ring().simplify(&scale);
-                    let neg_scale = -scale.clone();
-                    add_assign_hash(&upper.ring(), &mut bb, &mut hash, &neg_scale);
-                    xx.insert(ii-1, scale); // solution is indexed by new key after ordering.
-                }
+    for (key, val) in row {
+        let value = ringmetadata.multiply(scale, &val);
+        let remove = match target.get_mut(&key) {
+            Some(x) => { *x = ringmetadata.add(x, &value); ringmetadata.is_0(x) }
+            None => {
+                if !ringmetadata.is_0(&value) { target.insert(key, value); }
+                continue;
             }
-        }
-        ii -= 1;
+        };
+        if remove { target.remove(&key); }
     }
-    return xx;
 }
 
 /// Update the hash by adding scaled row to it
@@ -112,12 +200,12 @@ pub fn add_assign_hash<MinKey, SnzVal>(
     scale:          &SnzVal
 ) where
 MinKey: Eq+Hash+Ord+Clone,
-SnzVal: Clone + AddAssign + Mul<Output = SnzVal> + PartialEq + InvMod<Output = SnzVal>
+SnzVal: RingElement
 {
     for (key, val) in row.drain() {
-        let value = scale.clone()*val;
+        let value = ringmetadata.multiply(scale, &val);
         if let Some(x) = hash.get_mut(&key) {
-            *x += value;
+            *x = ringmetadata.add(x, &value);
             if ringmetadata.is_0(x) {
                 hash.remove(&key);
             }
@@ -142,7 +230,7 @@ pub fn multiply_hash_smoracle<MajKey, MinKey, SnzVal, Matrix>(
 where
 MinKey: PartialEq + Eq + Hash + Clone + Ord,
 MajKey: PartialEq + Eq + Hash + Clone + Ord,
-SnzVal: Clone + AddAssign + Mul<Output = SnzVal> + PartialEq + InvMod<Output = SnzVal>,
+SnzVal: RingElement,
 Matrix: SmOracle<MajKey, MinKey, SnzVal>
 {
     let mut product = HashMap::new();
@@ -169,7 +257,7 @@ pub fn multiply_hash_smoracle_version2<MajKey, MinKey, SnzVal, Matrix>(
 where
 MinKey: PartialEq + Eq + Hash + Clone + Ord,
 MajKey: PartialEq + Eq + Hash + Clone + Ord,
-SnzVal: Clone + AddAssign + Mul<Output = SnzVal> + PartialEq + InvMod<Output = SnzVal>,
+SnzVal: RingElement,
 Matrix: SmOracle<MajKey, MinKey, SnzVal>
 {
     let mut product = HashMap::new();
@@ -180,22 +268,90 @@ Matrix: SmOracle<MajKey, MinKey, SnzVal>
     return product;
 }
 
+/// The symmetric difference of two sorted lists of keys, as a sorted list
+///
+/// Over Z/2Z a sparse vector is determined by its support, and the sum of two vectors is the symmetric difference of their supports; this is how the Z/2Z paths of [`decomp_row`](crate::decomp_row::decomp_row) and [`decomp_col`](crate::decomp_col::decomp_col) add rows.  The keys are moved, not cloned.
+///
+/// # Parameters
+/// - `xx`, `yy`: lists of keys, each sorted in strictly increasing order
+pub fn symmetric_difference_sorted<Key: Ord>(xx: Vec<Key>, yy: Vec<Key>) -> Vec<Key> {
+    let mut output = Vec::with_capacity(xx.len() + yy.len());
+    let mut xx = xx.into_iter().peekable();
+    let mut yy = yy.into_iter().peekable();
+    while let (Some(x), Some(y)) = (xx.peek(), yy.peek()) {
+        match x.cmp(y) {
+            std::cmp::Ordering::Less => output.extend(xx.next()),
+            std::cmp::Ordering::Greater => output.extend(yy.next()),
+            std::cmp::Ordering::Equal => { xx.next(); yy.next(); }
+        }
+    }
+    output.extend(xx);
+    output.extend(yy);
+    output
+}
+
+/// Multiply a row vector with a smoracle matrix over the two element field, where both the vector and the product are represented by their supports
+///
+/// # Parameters
+/// - `support`: the indices in `index_pivot_majkey` where the row vector is nonzero
+/// - `index_pivot_majkey`: A vector maps index to the major key of the matrix
+/// - `matrix`: A sparse matrix oracle over Z/2Z
+///
+/// # Returns
+/// The keys where the product is nonzero, sorted in increasing order
+pub fn multiply_support_smoracle_gf2<MajKey, MinKey, SnzVal, Matrix>(
+    support:                &[usize],
+    index_pivot_majkey:     &[MajKey],
+    matrix:                 &Matrix
+) -> Vec<MinKey>
+where
+MinKey: PartialEq + Eq + Hash + Clone + Ord,
+MajKey: PartialEq + Eq + Hash + Clone + Ord,
+SnzVal: RingElement,
+Matrix: SmOracle<MajKey, MinKey, SnzVal>
+{
+    let ring = matrix.ring();
+    let mut keys: Vec<MinKey> = Vec::new();
+    for majind in support.iter() {
+        keys.extend(matrix.maj_itr(&index_pivot_majkey[*majind]).filter(|(_, val)| !ring.is_0(val)).map(|(key, _)| key));
+    }
+    keys.sort_unstable();
+
+    // a key belongs to the product if it appears in an odd number of rows
+    let mut product = Vec::with_capacity(keys.len());
+    let mut keys = keys.into_iter().peekable();
+    while let Some(key) = keys.next() {
+        let mut count = 1;
+        while keys.peek() == Some(&key) { keys.next(); count += 1; }
+        if count % 2 == 1 { product.push(key); }
+    }
+    product
+}
+
 /// Solve matrix*xx = hash
 ///
+/// Here `xx` is indexed by major keys, so the solution is a combination of major fields of `matrix`.  The system is
+/// inconsistent if we need to eliminate a minor key that has no pivot in `minkey_pivot_majkey` (or whose pivot entry
+/// is zero or not invertible); in that case we return `None`.  To solve several systems with the same matrix, see
+/// [`UMatch::solve_batch`](crate::umatch::UMatch::solve_batch).
+///
+/// The right hand side is reduced in place.  When a solution is found, `hash` is left empty; when `None` is returned,
+/// `hash` is left partially reduced, and its contents should not be used.  Pass a clone if the right hand side is needed afterwards.
+///
 /// # Parameters
 /// - `matrix`: a non-singular pivot sparse matrix oracle
-/// - `hash`: constant column sparse vector represented as a hash map
+/// - `hash`: constant column sparse vector represented as a hash map; it is consumed by the elimination (see above)
 /// - `minkey_pivot_majkey`:  A hash map indicates the pivots locations
 /// # Returns
-/// xx the unknow sparse vector represented as a hash map
+/// xx the unknow sparse vector represented as a hash map, or `None` if there is no solution
 pub fn solver<MinKey, MajKey, SnzVal, Matrix>(
     matrix:                 &Matrix,
     minkey_pivot_majkey:    &HashMap<MinKey, MajKey>,
-    mut hash:               &mut HashMap<MinKey, SnzVal>
-) -> HashMap<MajKey, SnzVal> where
+    hash:                   &mut HashMap<MinKey, SnzVal>
+) -> Option<HashMap<MajKey, SnzVal>> where
 MinKey: PartialEq + Eq + Hash + Clone + Ord,
 MajKey: PartialEq + Eq + Hash + Clone + Ord,
-SnzVal: Neg + Neg<Output=SnzVal> + AddAssign + Mul + Mul<Output=SnzVal> + Clone + InvMod + InvMod<Output = SnzVal> + PartialEq,
+SnzVal: RingElement,
 Matrix: SmOracle<MajKey, MinKey, SnzVal>
 {
     let mut heap: BinaryHeap<Reverse<MinKey>> = BinaryHeap::new();
@@ -206,23 +362,54 @@ Matrix: SmOracle<MajKey, MinKey, SnzVal>
     let mut xx: HashMap<MajKey, SnzVal> = HashMap::new();
 
     while let Some(Reverse(minkey)) = heap.pop() {
-        //if minkey_pivot_majkey.contains_key(&minkey) {
-            let majkey = &minkey_pivot_majkey[&minkey];
-            if let Some(value) = hash.remove(&minkey) {
-                let mut row: HashMap<MinKey, SnzVal> = matrix.maj_hash(majkey);
-                if let Some(dominator) = row.remove(&minkey) {
-                    if let Some(inverse) = matrix.ring().inverse(&dominator){
-                        let mut scale = -value.clone()*inverse.clone();
-                        scale = matrix.ring().simplify(&scale);
-                        update_heap_hash(&matrix.ring(), &mut heap, &mut hash, &mut row, &scale);
-                        xx.insert(majkey.clone(), value*inverse);
-                    }
-                }
-            }
-        //}
+        if let Some(value) = hash.remove(&minkey) {
+            if matrix.ring().is_0(&matrix.ring().simplify(&value)) { continue; }
+            let majkey = minkey_pivot_majkey.get(&minkey)?;
+            let mut row: HashMap<MinKey, SnzVal> = matrix.maj_hash(majkey);
+            let dominator = row.remove(&minkey)?;
+            let inverse = matrix.ring().inverse(&dominator)?;
+            let mut scale = matrix.ring().multiply(&matrix.ring().negate(&value), &inverse);
+            scale = matrix.ring().simplify(&scale);
+            update_heap_hash(matrix.ring(), &mut heap, hash, &mut row, &scale);
+            xx.insert(majkey.clone(), matrix.ring().multiply(&value, &inverse));
+        }
     }
-    return xx;
+    Some(xx)
 }
 
 
-//pub fn submatrix_of_smoracle<MajKey, MinKey, SnzVal, Matrix>(matrix: Matrix, maj_table: Vec<MajKey>, min_table: Vec<MinKey>) -> CSM<usize, SnzVal> { }
+/// Extract the submatrix of a sparse matrix oracle with given major and minor keys, as a CSM
+///
+/// # Parameters
+/// - `matrix`: the sparse matrix oracle
+/// - `maj_table`: the major keys of the submatrix; major field `ii` of the output corresponds to `maj_table[ii]`
+/// - `min_table`: the minor keys of the submatrix; minor index `jj` of the output corresponds to `min_table[jj]`
+/// # Returns
+/// the submatrix in CSM format, with minor indices sorted in ascending order within each major field
+/// # See also
+/// [`Submatrix`](crate::matrix::Submatrix), for a lazy version that does not relabel keys.
+pub fn submatrix_of_smoracle<MajKey, MinKey, SnzVal, Matrix>(
+    matrix:     &Matrix,
+    maj_table:  &[MajKey],
+    min_table:  &[MinKey]
+) -> CSM<usize, SnzVal> where
+MinKey: PartialEq + Eq + Hash + Clone,
+MajKey: PartialEq + Eq + Hash + Clone,
+SnzVal: Clone + PartialEq + Debug,
+Matrix: SmOracle<MajKey, MinKey, SnzVal>
+{
+    let minkey_2_index: HashMap<&MinKey, usize> = min_table.iter().enumerate().map(|(ii, key)| (key, ii)).collect();
+    let mut submatrix = CSM::new(matrix.maj_dim(), matrix.ring().clone());
+    for majkey in maj_table.iter() {
+        let mut field: Vec<(usize, SnzVal)> = matrix.maj_itr(majkey)
+            .filter_map(|(key, val)| minkey_2_index.get(&key).map(|ii| (*ii, val)))
+            .collect();
+        field.sort_by_key(|(ii, _)| *ii);
+        for (ii, val) in field {
+            submatrix.push_snzval(ii, val);
+        }
+        submatrix.majptr.push(submatrix.minind.len());
+        submatrix.nummaj += 1;
+    }
+    submatrix
+}
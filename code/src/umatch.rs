@@ -1,69 +1,949 @@
+/*!
 
+U-match factorization of a sparse matrix oracle
 
+# U-match factorization
 
-use persistence::matrix::{SmOracle, MajorDimension};
-use persistence::csm::CSM;
+A *U-match factorization* of a matrix *D* is an equation *TM = DS* where *T* and *S* are upper
+unitriangular and *M* is a generalized matching matrix (at most one nonzero entry per row and
+column).  The [`decomp_row`](crate::decomp_row::decomp_row) function does most of the work
+needed to obtain one: it returns the submatrix of *T<sup>-1</sup>* indexed by the matched rows,
+together with an [`Indexing`](crate::chx::Indexing) that records the matching.  The
+[`UMatch`](UMatch) struct wraps this output and gives lazy access to every matrix in the
+factorization:
 
+* *T* and *T<sup>-1</sup>* via [`cob_row`](UMatch::cob_row)
+* *S* and *S<sup>-1</sup>* via [`cob_col`](UMatch::cob_col)
+* *M* via [`matching`](UMatch::matching)
 
+Each of these is a sparse matrix oracle (that is, it implements the [`SmOracle`](crate::matrix::SmOracle)
+trait), so rows and columns are computed only when someone asks for them.
 
+To factor a matrix that does not come from a chain complex, use [`umatch_of`](umatch_of), which calls
+`decomp_row` for you and returns bases for the kernel, image and cokernel.
 
+**Conventions** We assume that `D` is row-major, and that the major keys were reduced in
+decreasing order (that is, `maj_to_reduce` was sorted in ascending order before it was passed
+to `decomp_row`, which pops keys from the end).  Under this assumption *T* is upper
+unitriangular with respect to the order on major keys, and *S* is upper unitriangular with
+respect to the order on minor keys.  Major and minor fields returned by the oracles in this
+module are sorted in ascending order of keys.
+
+```
+use exhact::matrix::{RingSpec, RingMetadata, MajorDimension, SmOracle};
+use exhact::csm::CSM;
+use exhact::decomp_row::decomp_row;
+use exhact::umatch::UMatch;
+use std::collections::HashMap;
+
+// a 3x3 matrix with coefficients in the field of order 3
+//
+// 1 1 0
+// 0 1 1
+// 1 2 1
+let ringmetadata = RingMetadata{
+    ringspec: RingSpec::Modulus(3),
+    identity_additive: 0,
+    identity_multiplicative: 1,
+};
+let mut matrix = CSM::new(MajorDimension::Row, ringmetadata);
+matrix.append_maj(&mut vec![(0, 1), (1, 1)].into_iter().collect());
+matrix.append_maj(&mut vec![(1, 1), (2, 1)].into_iter().collect());
+matrix.append_maj(&mut vec![(0, 1), (1, 2), (2, 1)].into_iter().collect());
+
+// factor the matrix
+let mut maj_to_reduce = vec![0, 1, 2];
+let (rowoper, indexing) = decomp_row(&matrix, &mut maj_to_reduce).unwrap();
+let umatch = UMatch::new(&matrix, &rowoper, &indexing);
+
+// the rows are linearly dependent, so the rank is 2; since `decomp_row` reduces rows
+// in reverse order, row 0 is the one left unmatched
+assert_eq!(umatch.rank(), 2);
+
+// check that row 0 of T^{-1} D equals row 0 of M S^{-1} (which is zero)
+let tinv = umatch.cob_row(true);
+let sinv = umatch.cob_col(true);
+let mut lhs: HashMap<usize, i16> = HashMap::new();
+for (majkey, coeff) in tinv.maj_itr(&0) {
+    for (minkey, val) in matrix.maj_itr(&majkey) {
+        *lhs.entry(minkey).or_insert(0) += coeff * val;
+    }
+}
+lhs.retain(|_, val| *val % 3 != 0);
+assert!(lhs.is_empty());
+assert_eq!(umatch.matching().maj_itr(&0).count(), 0);
+assert_eq!(sinv.maj_itr(&0).next().unwrap().0, 0);
+```
+
+*/
+
+
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::hash::Hash;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+use crate::matrix::{SmOracle, RingElement, Semiring, Ring, DivisionRing, MajorDimension, RingMetadata, DecompError};
+use crate::csm::{CSM, DualCsm};
+use crate::chx::Indexing;
+use crate::decomp_row::decomp_row;
+use crate::solver::{add_assign_hash, add_assign_btree, multiply_hash_smoracle_version2, triangular_solve, Triangle, SolveSide};
 
 /// Provides access to the upper triangular matrices (and their inverses) in an U-match
 /// decomposiion.
 ///
-/// The commands associated with this struct (e.g. those allowing one to invert or change major
-/// dimension of a row/column operation matrix) can be much more efficient than the generic
-/// options.
-struct UMatch<MajKey, MinKey, SnzVal>{
-    smoracle: &SmOracle<MajKey, MinKey, SnzVal>,    // the matrix to factor
-    factor_data: CSM<usize, SnzVal>,                // partial change of basis matrix
-    pivot_bijections: persistence::chx::Indexing    // indexing information
+/// The commands associated with this struct (e.g. those allowing one to invert a row/column
+/// operation matrix) can be much more efficient than the generic options.
+///
+/// The row operation matrix may be any oracle with `usize` keys.  Many queries (for example the
+/// columns of *S<sup>-1</sup>*) need columns of this matrix, so pass a
+/// [`DualCsm`](crate::csm::DualCsm) rather than a plain `CSM` when these are frequent.
+pub struct UMatch<'a, MajKey, MinKey, SnzVal, Matrix, RowOper = CSM<usize, SnzVal>> where
+SnzVal: Clone
+{
+    smoracle: &'a Matrix,                           // the matrix to factor
+    factor_data: &'a RowOper,                       // partial change of basis matrix
+    pivot_bijections: &'a Indexing<MinKey, MajKey>, // indexing information
+    pivot_values: Cow<'a, [SnzVal]>,                // nonzero entries of the matching matrix
+    phantom: PhantomData<(MajKey, MinKey)>
 }
 
+/// Methods of UMatch struct
+impl<'a, MajKey, MinKey, SnzVal, Matrix, RowOper> UMatch<'a, MajKey, MinKey, SnzVal, Matrix, RowOper> where
+MajKey: PartialEq + Eq + Hash + Clone + Ord + Debug,
+MinKey: PartialEq + Eq + Hash + Clone + Ord + Debug,
+SnzVal: RingElement + Debug,
+Matrix: SmOracle<MajKey, MinKey, SnzVal>,
+RowOper: SmOracle<usize, usize, SnzVal>
+{
+
+    /// Wrap the output of [`decomp_row`](crate::decomp_row::decomp_row) in a U-match.
+    ///
+    /// # Parameters
+    /// - `smoracle`: the (row-major) matrix that was factored
+    /// - `factor_data`: the row operation matrix returned by `decomp_row`, or a [`DualCsm`](crate::csm::DualCsm) that wraps it
+    /// - `pivot_bijections`: the indexing returned by `decomp_row`
+    pub fn new(
+        smoracle:           &'a Matrix,
+        factor_data:        &'a RowOper,
+        pivot_bijections:   &'a Indexing<MinKey, MajKey>
+    ) -> UMatch<'a, MajKey, MinKey, SnzVal, Matrix, RowOper> {
+
+        // the nonzero coefficient of row `index` of T^{-1}D in column `index_2_minkey[index]`
+        let rank = pivot_bijections.index_2_majkey.len();
+        let mut pivot_values = Vec::with_capacity(rank);
+        for index in 0..rank {
+            let minkey = &pivot_bijections.index_2_minkey[index];
+            let mut value = smoracle.ring().identity_additive.clone();
+            for (majind, coeff) in factor_data.maj_itr(&index) {
+                if let Some(entry) = smoracle.entry(&pivot_bijections.index_2_majkey[majind], minkey) {
+                    value = smoracle.ring().add(&value, &smoracle.ring().multiply(&coeff, &entry));
+                }
+            }
+            pivot_values.push(smoracle.ring().simplify(&value));
+        }
+
+        UMatch {
+            smoracle,
+            factor_data,
+            pivot_bijections,
+            pivot_values: Cow::Owned(pivot_values),
+            phantom: PhantomData
+        }
+    }
+
+    /// Wrap the output of [`decomp_row`](crate::decomp_row::decomp_row) in a U-match, reusing the pivot values of an earlier U-match of the same matrix.
+    ///
+    /// [`new`](UMatch::new) reads every row of `factor_data` to find the nonzero entries of the matching matrix; this
+    /// constructor takes them from `pivot_values` instead, so it runs in constant time.  This is useful when a U-match
+    /// is needed for many queries but cannot be kept alive between them.
+    ///
+    /// # Parameters
+    /// - `smoracle`, `factor_data`, `pivot_bijections`: as for [`new`](UMatch::new)
+    /// - `pivot_values`: the [`pivot_values`](UMatch::pivot_values) of a U-match built from the same arguments
+    pub fn with_pivot_values(
+        smoracle:           &'a Matrix,
+        factor_data:        &'a RowOper,
+        pivot_bijections:   &'a Indexing<MinKey, MajKey>,
+        pivot_values:       &'a [SnzVal]
+    ) -> UMatch<'a, MajKey, MinKey, SnzVal, Matrix, RowOper> {
+        assert_eq!(pivot_values.len(), pivot_bijections.index_2_majkey.len(), "there should be one pivot value for each matched major key");
+        UMatch {
+            smoracle,
+            factor_data,
+            pivot_bijections,
+            pivot_values: Cow::Borrowed(pivot_values),
+            phantom: PhantomData
+        }
+    }
+
+    /// The coefficient ring of the factored matrix.
+    pub fn ring( &self ) -> &RingMetadata<SnzVal> {
+        self.smoracle.ring()
+    }
+
+    /// The matrix being factored.
+    pub fn smoracle( &self ) -> &Matrix {
+        self.smoracle
+    }
+
+    /// The indexing information returned by `decomp_row`.
+    pub fn pivot_bijections( &self ) -> &Indexing<MinKey, MajKey> {
+        self.pivot_bijections
+    }
+
+    /// The nonzero entries of the matching matrix; entry `i` is in the row of `pivot_bijections().index_2_majkey[i]`.
+    pub fn pivot_values( &self ) -> &[SnzVal] {
+        &self.pivot_values
+    }
+
+    /// Number of nonzero entries in the matching matrix (equivalently, the rank of the factored matrix).
+    pub fn rank( &self ) -> usize {
+        self.pivot_values.len()
+    }
+
+    /// Return an upper-unitriangular row-operation matrix, namely *T* (if `invert` is false) or *T<sup>-1</sup>* (if `invert` is true).
+    ///
+    /// The output is a row-major oracle with rows and columns indexed by major keys of the factored matrix.
+    pub fn cob_row( &self, invert: bool ) -> UMatchCobRow<'_, 'a, MajKey, MinKey, SnzVal, Matrix, RowOper> {
+        UMatchCobRow { umatch: self, invert }
+    }
+
+    /// Return an upper-unitriangular column-operation matrix, namely *S* (if `invert` is false) or *S<sup>-1</sup>* (if `invert` is true).
+    ///
+    /// The output is a row-major oracle with rows and columns indexed by minor keys of the factored matrix.
+    pub fn cob_col( &self, invert: bool ) -> UMatchCobCol<'_, 'a, MajKey, MinKey, SnzVal, Matrix, RowOper> {
+        UMatchCobCol { umatch: self, invert }
+    }
+
+    /// Return the matching matrix *M*.
+    pub fn matching( &self ) -> UMatchMatching<'_, 'a, MajKey, MinKey, SnzVal, Matrix, RowOper> {
+        UMatchMatching { umatch: self }
+    }
+
+    // --------------------------------------------------------------------------------------------
+    // PRIMITIVE OPERATIONS ON THE ROW OPERATION MATRIX
+    // --------------------------------------------------------------------------------------------
+
+    /// Inverse of the pivot value with given index
+    fn pivot_inverse( &self, index: usize ) -> SnzVal {
+        self.ring().inverse(&self.pivot_values[index])
+            .expect("the nonzero entries of a matching matrix must be invertible")
+    }
+
+    /// Column `index` of `factor_data`, represented as a hash map
+    fn factor_data_column( &self, index: usize ) -> HashMap<usize, SnzVal> {
+        self.factor_data.min_itr(&index).collect()
+    }
+
+    /// Row `index` of T^{-1}D, where `index` is the index of a matched major key
+    fn reduced_row( &self, index: usize ) -> HashMap<MinKey, SnzVal> {
+        multiply_hash_smoracle_version2(&self.factor_data.maj_hash(&index), &self.pivot_bijections.index_2_majkey, self.smoracle)
+    }
+
+    // --------------------------------------------------------------------------------------------
+    // ROWS AND COLUMNS OF T, T^{-1}, S, S^{-1}
+    // --------------------------------------------------------------------------------------------
+
+    /// Row `majkey` of T^{-1}
+    ///
+    /// Rows of matched keys are read directly from `factor_data`.  For an unmatched key, we
+    /// reduce the corresponding row of the factored matrix against the matched rows, just as
+    /// `decomp_row` did before it discarded the result.
+    pub fn tinv_row( &self, majkey: &MajKey ) -> HashMap<MajKey, SnzVal> {
+        let indexing = self.pivot_bijections;
+        let mut output = HashMap::new();
+        if let Some(index) = indexing.majkey_2_index.get(majkey) {
+            for (majind, val) in self.factor_data.maj_itr(index) {
+                output.insert(indexing.index_2_majkey[majind].clone(), val);
+            }
+            return output;
+        }
+
+        output.insert(majkey.clone(), self.ring().identity_multiplicative.clone());
+        let mut residual: BTreeMap<MinKey, SnzVal> = self.smoracle.maj_itr(majkey).collect();
+        while let Some((minkey, leading_entry)) = residual.pop_first() {
+            if self.ring().is_0(&leading_entry) { continue; }
+            let index = *indexing.minkey_2_index.get(&minkey)
+                .expect("row is not in the span of the matched rows; was it omitted from maj_to_reduce?");
+            let scale = self.ring().multiply(&self.ring().negate(&leading_entry), &self.pivot_inverse(index));
+            let mut row = HashMap::new();
+            for (majind, val) in self.factor_data.maj_itr(&index) {
+                row.insert(indexing.index_2_majkey[majind].clone(), val);
+            }
+            add_assign_hash(self.ring(), &mut output, &mut row, &scale);
+            let mut reduced = self.reduced_row(index);
+            reduced.remove(&minkey);
+            add_assign_btree(self.ring(), &mut residual, reduced, &scale);
+        }
+        output
+    }
 
-impl UMatch<MajKey, MinKey, SnzVal> {
-   
+    /// Column `majkey` of T^{-1}, obtained by solving T x = e_majkey
+    pub fn tinv_col( &self, majkey: &MajKey ) -> HashMap<MajKey, SnzVal> {
+        if !self.pivot_bijections.majkey_2_index.contains_key(majkey) {
+            let mut output = HashMap::new();
+            output.insert(majkey.clone(), self.ring().identity_multiplicative.clone());
+            return output;
+        }
+        self.solve_unit_vector(&self.cob_row(false), SolveSide::Right, majkey)
+    }
 
-    /// Set the major dimension of the CSM stored in the `factor_data` field.
+    /// Column `majkey` of T
     ///
-    /// Changing the major dimension may transpose the `factor_data` CSM stored internally to the struct.  This can dramatically increase the efficiency of the change of basis oracles.
-    /// 
-    fn set_major_dim( &self, MajorDimension ) {
-        self.seed_data = transpose(self.cob);
+    /// If `majkey` is matched to `minkey`, then this column equals D S[:,minkey] divided by the matched coefficient.
+    pub fn t_col( &self, majkey: &MajKey ) -> HashMap<MajKey, SnzVal> {
+        let mut output = HashMap::new();
+        match self.pivot_bijections.majkey_2_index.get(majkey) {
+            None => {
+                output.insert(majkey.clone(), self.ring().identity_multiplicative.clone());
+            }
+            Some(index) => {
+                let scale = self.pivot_inverse(*index);
+                let minkey = &self.pivot_bijections.index_2_minkey[*index];
+                for (key, val) in self.s_col(minkey) {
+                    let mut column = self.smoracle.min_hash(&key);
+                    let coeff = self.ring().multiply(&val, &scale);
+                    add_assign_hash(self.ring(), &mut output, &mut column, &coeff);
+                }
+            }
+        }
+        output
+    }
+
+    /// Row `majkey` of T, obtained by solving x T^{-1} = e_majkey
+    pub fn t_row( &self, majkey: &MajKey ) -> HashMap<MajKey, SnzVal> {
+        self.solve_unit_vector(&self.cob_row(true), SolveSide::Left, majkey)
+    }
+
+    /// Row `minkey` of S^{-1}
+    pub fn sinv_row( &self, minkey: &MinKey ) -> HashMap<MinKey, SnzVal> {
+        match self.pivot_bijections.minkey_2_index.get(minkey) {
+            None => {
+                let mut output = HashMap::new();
+                output.insert(minkey.clone(), self.ring().identity_multiplicative.clone());
+                output
+            }
+            Some(index) => {
+                let scale = self.pivot_inverse(*index);
+                let mut reduced = self.reduced_row(*index);
+                let mut output = HashMap::new();
+                add_assign_hash(self.ring(), &mut output, &mut reduced, &scale);
+                output
+            }
+        }
+    }
+
+    /// Column `minkey` of S^{-1}
+    pub fn sinv_col( &self, minkey: &MinKey ) -> HashMap<MinKey, SnzVal> {
+        let indexing = self.pivot_bijections;
+
+        // column `minkey` of T^{-1}D, indexed by integers
+        let mut column = HashMap::new();
+        for (majkey, val) in self.smoracle.min_itr(minkey) {
+            if let Some(majind) = indexing.majkey_2_index.get(&majkey) {
+                let mut factor_column = self.factor_data_column(*majind);
+                add_assign_hash(self.ring(), &mut column, &mut factor_column, &val);
+            }
+        }
+
+        let mut output = HashMap::new();
+        for (index, val) in column.drain() {
+            let scaled = self.ring().multiply(&val, &self.pivot_inverse(index));
+            output.insert(indexing.index_2_minkey[index].clone(), scaled);
+        }
+        if !indexing.minkey_2_index.contains_key(minkey) {
+            output.insert(minkey.clone(), self.ring().identity_multiplicative.clone());
+        }
+        output
+    }
+
+    /// Column `minkey` of S, obtained by solving S^{-1} x = e_minkey
+    pub fn s_col( &self, minkey: &MinKey ) -> HashMap<MinKey, SnzVal> {
+        self.solve_unit_vector(&self.cob_col(true), SolveSide::Right, minkey)
     }
 
+    /// Row `minkey` of S, obtained by solving x S^{-1} = e_minkey
+    pub fn s_row( &self, minkey: &MinKey ) -> HashMap<MinKey, SnzVal> {
+        if !self.pivot_bijections.minkey_2_index.contains_key(minkey) {
+            let mut output = HashMap::new();
+            output.insert(minkey.clone(), self.ring().identity_multiplicative.clone());
+            return output;
+        }
+        self.solve_unit_vector(&self.cob_col(true), SolveSide::Left, minkey)
+    }
 
-    /// Get the major dimension of the `factor_data` attribute. 
-    fn get_major_dim( &self ) -> &MajorDimension {
-        &self.factor_data.majdim
+    /// Solve `x U = e_key` (if `side` is `Left`) or `U x = e_key` (if `side` is `Right`), where `U` is one of the upper unitriangular change of basis matrices
+    fn solve_unit_vector<Key, Oracle>( &self, matrix: &Oracle, side: SolveSide, key: &Key ) -> HashMap<Key, SnzVal> where
+    Key: PartialEq + Eq + Hash + Clone + Ord + Debug,
+    Oracle: SmOracle<Key, Key, SnzVal>
+    {
+        let mut unit = HashMap::new();
+        unit.insert(key.clone(), self.ring().identity_multiplicative.clone());
+        triangular_solve(matrix, Triangle::Upper, side, true, &unit)
+            .expect("the change of basis matrices of a U-match should be upper unitriangular")
     }
-    
-    /// Return an upper-unitriangular row/column-operation matrix.
+
+    // --------------------------------------------------------------------------------------------
+    // SOLVING LINEAR SYSTEMS
+    // --------------------------------------------------------------------------------------------
+
+    /// Solve `D x = b` for `x`, where `D` is the factored matrix and `b` is a column vector, or return `None` if there is no solution
     ///
-    /// The major dimension of the output matrix will be the major dimension of the `factor_data`
-    /// attribute (not necessarily the major dimension of the matrix being factored).
+    /// Since `D = T M S^{-1}`, a solution exists if and only if `T^{-1} b` is supported on matched rows; in this case
+    /// `x = S M^{-1} T^{-1} b` is a solution, and it is supported on minor keys no larger than the largest matched
+    /// minor key that `M^{-1} T^{-1} b` uses.  In particular, if the minor keys are ordered by filtration, then
+    /// no solution is supported on an earlier sublevel set than this one.
     ///
     /// # Parameters
-    /// 
-    /// * `which_side` -- row versus column operation matrix
-    /// * `invert` -- if true, then return the inverse of the operation matrix
+    /// - `bb`: the column vector `b`, indexed by major keys
+    pub fn solve( &self, bb: &HashMap<MajKey, SnzVal> ) -> Option<HashMap<MinKey, SnzVal>> {
+        self.solve_using(bb, &self.cob_row(false), &self.cob_col(false))
+    }
+
+    /// Solve `D x = b` for each of several column vectors `b`, reusing the factorization
+    ///
+    /// Entry `i` of the output is the solution returned by [`solve`](UMatch::solve) for `bbs[i]`; in particular it
+    /// is `None` if the system is inconsistent, that is, if `bbs[i]` is not in the image of `D`.  The columns of
+    /// *T* and *S* computed for one right hand side are kept and reused for the others, so each column is computed
+    /// at most once per call, however many right hand sides need it.
+    ///
+    /// The cache is a `RefCell` that lives only for the duration of the call, so it is not `Sync`: a batch is always
+    /// solved on the calling thread, and the cached columns are not shared between threads or between calls.  To solve
+    /// in parallel, call [`solve`](UMatch::solve) for each right hand side from several threads instead: a `UMatch` only
+    /// holds shared references and owned data, so it is `Sync` whenever the factored matrix oracle and the row operation
+    /// oracle are.
+    ///
+    /// ```
+    /// use exhact::matrix::{RingSpec, RingMetadata, MajorDimension};
+    /// use exhact::csm::CSM;
+    /// use exhact::umatch::umatch_of;
+    /// use std::collections::HashMap;
+    ///
+    /// // the 2x2 matrix [[1, 1], [2, 2]] with coefficients in the field of order 3
+    /// let ringmetadata = RingMetadata{ ringspec: RingSpec::Modulus(3), identity_additive: 0, identity_multiplicative: 1 };
+    /// let mut matrix = CSM::new(MajorDimension::Row, ringmetadata);
+    /// matrix.append_maj(&mut vec![(0, 1), (1, 1)].into_iter().collect());
+    /// matrix.append_maj(&mut vec![(0, 2), (1, 2)].into_iter().collect());
+    /// let factored = umatch_of(&matrix, &[0, 1], &[0, 1]).unwrap();
+    ///
+    /// // (1, 2) is the first column of the matrix, but (1, 0) is not in its image
+    /// let bbs: Vec<HashMap<usize, i16>> = vec![
+    ///     vec![(0, 1), (1, 2)].into_iter().collect(),
+    ///     vec![(0, 1)].into_iter().collect(),
+    /// ];
+    /// let solutions = factored.umatch().solve_batch(&bbs);
+    /// assert!(solutions[0].is_some());
+    /// assert!(solutions[1].is_none());
+    /// ```
+    pub fn solve_batch( &self, bbs: &[HashMap<MajKey, SnzVal>] ) -> Vec<Option<HashMap<MinKey, SnzVal>>> {
+        let t = MinorFieldCache::new(self.cob_row(false));
+        let s = MinorFieldCache::new(self.cob_col(false));
+        bbs.iter().map(|bb| self.solve_using(bb, &t, &s)).collect()
+    }
+
+    /// Return a generalized inverse *D<sup>+</sup>* of the factored matrix *D*, that is, a matrix such that *D D<sup>+</sup> D = D*.
+    ///
+    /// The oracle represents *D<sup>+</sup> = S M<sup>+</sup> T<sup>-1</sup>*, where *M<sup>+</sup>* is the transpose of *M* with each
+    /// nonzero entry replaced by its inverse.  Its rows are indexed by minor keys and its columns by major keys of *D*,
+    /// and they are computed only when someone asks for them.  If `b` is in the image of *D*, then *D<sup>+</sup> b*
+    /// equals the solution returned by [`solve`](UMatch::solve); otherwise it is a vector that does not solve the system.
+    ///
+    /// ```
+    /// use exhact::matrix::{RingSpec, RingMetadata, MajorDimension, SmOracle};
+    /// use exhact::csm::CSM;
+    /// use exhact::umatch::umatch_of;
+    ///
+    /// // the 2x3 matrix [[1, 1, 0], [2, 2, 1]] with coefficients in the field of order 3
+    /// let ringmetadata = RingMetadata{ ringspec: RingSpec::Modulus(3), identity_additive: 0, identity_multiplicative: 1 };
+    /// let mut matrix = CSM::new(MajorDimension::Row, ringmetadata);
+    /// matrix.append_maj(&mut vec![(0, 1), (1, 1)].into_iter().collect());
+    /// matrix.append_maj(&mut vec![(0, 2), (1, 2), (2, 1)].into_iter().collect());
+    /// let factored = umatch_of(&matrix, &[0, 1], &[0, 1, 2]).unwrap();
+    /// let umatch = factored.umatch();
+    /// let ginv = umatch.generalized_inverse();
+    ///
+    /// // check that D D^+ D = D, one column of D at a time
+    /// for col in 0..3 {
+    ///     let column = matrix.min_hash(&col);
+    ///     let mut ginv_column = vec![0i16; 3];
+    ///     for (row, val) in column.iter() {
+    ///         for (key, coeff) in ginv.min_itr(row) { ginv_column[key] += coeff * val; }
+    ///     }
+    ///     for row in 0..2 {
+    ///         let entry: i16 = matrix.maj_itr(&row).map(|(key, val)| val * ginv_column[key]).sum();
+    ///         assert_eq!((entry - column.get(&row).cloned().unwrap_or(0)) % 3, 0);
+    ///     }
+    /// }
+    /// ```
+    pub fn generalized_inverse( &self ) -> UMatchGeneralizedInverse<'_, 'a, MajKey, MinKey, SnzVal, Matrix, RowOper> {
+        UMatchGeneralizedInverse { umatch: self }
+    }
+
+    /// Solve `D x = b`, reading columns of *T* and *S* from the given oracles
+    ///
+    /// Returns `None` if `b` is not in the image of `D`, and also if `t` turns out not to be upper unitriangular (which would be a bug in the oracle).
+    fn solve_using<TT, SS>( &self, bb: &HashMap<MajKey, SnzVal>, t: &TT, s: &SS ) -> Option<HashMap<MinKey, SnzVal>> where
+    TT: SmOracle<MajKey, MajKey, SnzVal>,
+    SS: SmOracle<MinKey, MinKey, SnzVal>
+    {
+        let indexing = self.pivot_bijections;
+        let tinv_b = triangular_solve(t, Triangle::Upper, SolveSide::Right, true, bb).ok()?;
+
+        // y = M^{-1} T^{-1} b
+        let mut yy = HashMap::new();
+        for (majkey, val) in tinv_b {
+            let index = indexing.majkey_2_index.get(&majkey)?;
+            let scaled = self.ring().multiply(&val, &self.pivot_inverse(*index));
+            yy.insert(indexing.index_2_minkey[*index].clone(), scaled);
+        }
+
+        // x = S y
+        let mut xx = HashMap::new();
+        for (minkey, val) in yy {
+            let mut column = s.min_hash(&minkey);
+            add_assign_hash(self.ring(), &mut xx, &mut column, &val);
+        }
+        Some(nonzero_entries(self.ring(), xx))
+    }
+
+    /// Apply M^+ to a vector indexed by major keys, dropping entries of unmatched keys
+    fn matching_pseudoinverse( &self, vector: HashMap<MajKey, SnzVal> ) -> HashMap<MinKey, SnzVal> {
+        let indexing = self.pivot_bijections;
+        vector.into_iter()
+            .filter_map(|(majkey, val)| {
+                let index = indexing.majkey_2_index.get(&majkey)?;
+                Some((indexing.index_2_minkey[*index].clone(), self.ring().multiply(&val, &self.pivot_inverse(*index))))
+            })
+            .collect()
+    }
+
+    /// Apply the transpose of M^+ to a vector indexed by minor keys, dropping entries of unmatched keys
+    fn matching_pseudoinverse_transpose( &self, vector: HashMap<MinKey, SnzVal> ) -> HashMap<MajKey, SnzVal> {
+        let indexing = self.pivot_bijections;
+        vector.into_iter()
+            .filter_map(|(minkey, val)| {
+                let index = indexing.minkey_2_index.get(&minkey)?;
+                Some((indexing.index_2_majkey[*index].clone(), self.ring().multiply(&val, &self.pivot_inverse(*index))))
+            })
+            .collect()
+    }
+}
+
+
+// ------------------------------------------------------------------------------------------------
+// U-MATCH OF AN ARBITRARY MATRIX
+// ------------------------------------------------------------------------------------------------
+
+
+/// A finite sparse matrix together with the output of [`decomp_row`](crate::decomp_row::decomp_row)
+///
+/// Built by [`umatch_of`](umatch_of).  Unlike [`UMatch`](UMatch), this struct owns the row operation matrix
+/// and the indexing, so it can be returned from a function; call [`umatch`](FactoredMatrix::umatch) to
+/// access the full factorization.
+pub struct FactoredMatrix<'a, MajKey, MinKey, SnzVal, Matrix> where
+SnzVal: Clone
+{
+    pub smoracle: &'a Matrix,                       // the factored matrix
+    pub maj_keys: Vec<MajKey>,                      // major keys, in ascending order
+    pub min_keys: Vec<MinKey>,                      // minor keys, in ascending order
+    pub rowoper: DualCsm<usize, SnzVal>,            // row operation matrix returned by `decomp_row`
+    pub indexing: Indexing<MinKey, MajKey>,         // indexing returned by `decomp_row`
+}
+
+/// Factor a finite sparse matrix, to obtain its rank, nullity, and bases for its kernel, image and cokernel
+///
+/// The matrix need not come from a chain complex: any row-major oracle will do, for example a
+/// [`CSM`](crate::csm::CSM) read from a file with [`read_csm_mtx`](crate::mtx::read_csm_mtx) (which also
+/// returns the number of columns).  We regard the matrix as a linear map that sends a vector indexed by
+/// minor keys to a vector indexed by major keys, that is, *x* &#8614; *Dx*.
+///
+/// ```
+/// use exhact::matrix::{RingSpec, RingMetadata, MajorDimension, SmOracle, DecompError};
+/// use exhact::csm::CSM;
+/// use exhact::umatch::umatch_of;
+///
+/// // a 3x4 matrix with coefficients in the field of order 3; the last column is zero
+/// //
+/// // 1 1 0 0
+/// // 0 1 1 0
+/// // 1 2 1 0
+/// let ringmetadata = RingMetadata{
+///     ringspec: RingSpec::Modulus(3),
+///     identity_additive: 0,
+///     identity_multiplicative: 1,
+/// };
+/// let mut matrix = CSM::new(MajorDimension::Row, ringmetadata);
+/// matrix.append_maj(&mut vec![(0, 1), (1, 1)].into_iter().collect());
+/// matrix.append_maj(&mut vec![(1, 1), (2, 1)].into_iter().collect());
+/// matrix.append_maj(&mut vec![(0, 1), (1, 2), (2, 1)].into_iter().collect());
+///
+/// let factored = umatch_of(&matrix, &[0, 1, 2], &[0, 1, 2, 3]).unwrap();
+/// assert_eq!(factored.rank(), 2);
+/// assert_eq!(factored.nullity(), Ok(2));
+/// assert_eq!(factored.image().len(), 2);
+/// assert_eq!(factored.cokernel().len(), 1);
+///
+/// // every kernel vector is sent to zero
+/// let kernel = factored.kernel();
+/// assert_eq!(kernel.len(), 2);
+/// for vector in kernel.iter() {
+///     for row in 0..3 {
+///         let product: i16 = matrix.maj_itr(&row).map(|(col, val)| val * vector.get(&col).cloned().unwrap_or(0)).sum();
+///         assert_eq!(product % 3, 0);
+///     }
+/// }
+///
+/// // the pivots of the matrix lie in two different columns, so they cannot all be in column 0
+/// assert!(matches!(umatch_of(&matrix, &[0, 1, 2], &[0]), Err(DecompError::PivotOutsideMinKeys(_))));
+///
+/// // column-major oracles are rejected
+/// let columns: CSM<usize, i16> = CSM::new(MajorDimension::Col, matrix.ring().clone());
+/// assert!(matches!(umatch_of(&columns, &[], &[]), Err(DecompError::ColumnMajorOracle)));
+/// ```
+///
+/// # Parameters
+/// - `matrix`: a row-major sparse matrix oracle (wrap a column-major oracle in [`Transposed`](crate::matrix::Transposed) to factor the transpose instead)
+/// - `maj_keys`: every major key of the matrix (for example, the row indices `0..nummaj` of a CSM); repeated keys are ignored
+/// - `min_keys`: every minor key of the matrix, including those of columns that are identically zero; repeated keys are ignored
+///
+/// # Errors
+/// - [`DecompError::ColumnMajorOracle`](crate::matrix::DecompError::ColumnMajorOracle) if the oracle is column-major
+/// - [`DecompError::PivotOutsideMinKeys`](crate::matrix::DecompError::PivotOutsideMinKeys) if a pivot of the matrix lies in a column that is not listed in `min_keys`
+/// - [`DecompError::NotAField`](crate::matrix::DecompError::NotAField) if the coefficient ring is not a field
+pub fn umatch_of<'a, MajKey, MinKey, SnzVal, Matrix>(
+    matrix:         &'a Matrix,
+    maj_keys:       &[MajKey],
+    min_keys:       &[MinKey]
+) -> Result<FactoredMatrix<'a, MajKey, MinKey, SnzVal, Matrix>, DecompError> where
+MajKey: PartialEq + Eq + Hash + Clone + Ord + Debug,
+MinKey: PartialEq + Eq + Hash + Clone + Ord + Debug,
+SnzVal: RingElement + Debug,
+Matrix: SmOracle<MajKey, MinKey, SnzVal>
+{
+    if matrix.maj_dim() != MajorDimension::Row {
+        return Err(DecompError::ColumnMajorOracle);
+    }
+
+    let mut maj_keys = maj_keys.to_vec();
+    let mut min_keys = min_keys.to_vec();
+    maj_keys.sort();
+    maj_keys.dedup();
+    min_keys.sort();
+    min_keys.dedup();
+
+    // `decomp_row` pops keys from the end, so that rows are reduced in decreasing order
+    let mut maj_to_reduce = maj_keys.clone();
+    let (rowoper, indexing) = decomp_row(matrix, &mut maj_to_reduce)?;
+
+    // every matched minor key is listed, so the rank is at most the number of minor keys
+    if let Some(minkey) = indexing.index_2_minkey.iter().find(|minkey| min_keys.binary_search(minkey).is_err()) {
+        return Err(DecompError::PivotOutsideMinKeys(format!("{:?}", minkey)));
+    }
+    Ok(FactoredMatrix { smoracle: matrix, maj_keys, min_keys, rowoper: DualCsm::new(rowoper), indexing })
+}
+
+/// Methods of FactoredMatrix struct
+impl<'a, MajKey, MinKey, SnzVal, Matrix> FactoredMatrix<'a, MajKey, MinKey, SnzVal, Matrix> where
+MajKey: PartialEq + Eq + Hash + Clone + Ord + Debug,
+MinKey: PartialEq + Eq + Hash + Clone + Ord + Debug,
+SnzVal: RingElement + Debug,
+Matrix: SmOracle<MajKey, MinKey, SnzVal>
+{
+    /// The U-match factorization of the matrix
+    pub fn umatch( &self ) -> UMatch<'_, MajKey, MinKey, SnzVal, Matrix, DualCsm<usize, SnzVal>> {
+        UMatch::new(self.smoracle, &self.rowoper, &self.indexing)
+    }
+
+    /// The rank of the matrix
+    pub fn rank( &self ) -> usize {
+        self.indexing.index_2_majkey.len()
+    }
+
+    /// The dimension of the kernel, that is, the number of minor keys minus the rank
+    ///
+    /// Returns [`DecompError::RankExceedsMinKeys`](crate::matrix::DecompError::RankExceedsMinKeys) if the rank exceeds the number of minor keys; this
+    /// cannot happen for a struct built by [`umatch_of`](umatch_of), which checks that every matched minor key is listed in `min_keys`.
+    pub fn nullity( &self ) -> Result<usize, DecompError> {
+        let rank = self.rank();
+        let num_min_keys = self.min_keys.len();
+        num_min_keys.checked_sub(rank).ok_or(DecompError::RankExceedsMinKeys{ rank, num_min_keys })
+    }
+
+    /// A basis for the kernel, indexed by minor keys
+    ///
+    /// Since *DS = TM*, the columns of *S* indexed by unmatched minor keys are sent to zero; they are
+    /// linearly independent because *S* is invertible.
+    pub fn kernel( &self ) -> Vec<HashMap<MinKey, SnzVal>> {
+        let umatch = self.umatch();
+        self.min_keys.iter()
+            .filter(|minkey| !self.indexing.minkey_2_index.contains_key(minkey))
+            .map(|minkey| nonzero_entries(self.smoracle.ring(), umatch.s_col(minkey)))
+            .collect()
+    }
+
+    /// A basis for the image, indexed by major keys
+    ///
+    /// This is the set of columns of *T* indexed by matched major keys.
+    pub fn image( &self ) -> Vec<HashMap<MajKey, SnzVal>> {
+        let umatch = self.umatch();
+        self.maj_keys.iter()
+            .filter(|majkey| self.indexing.majkey_2_index.contains_key(majkey))
+            .map(|majkey| nonzero_entries(self.smoracle.ring(), umatch.t_col(majkey)))
+            .collect()
+    }
+
+    /// A basis for the cokernel, indexed by major keys
     ///
-    fn cob( &self, which_side : MajorDimension, invert : bool ) -> 
-        UMatchCob < MajKey, Minkey, SnazVal > {
+    /// The columns of *T* indexed by unmatched major keys are standard unit vectors, and together with
+    /// the [`image`](FactoredMatrix::image) they form a basis; so the unit vectors of unmatched major keys
+    /// project to a basis of the cokernel.
+    pub fn cokernel( &self ) -> Vec<HashMap<MajKey, SnzVal>> {
+        self.maj_keys.iter()
+            .filter(|majkey| !self.indexing.majkey_2_index.contains_key(majkey))
+            .map(|majkey| {
+                let mut vector = HashMap::new();
+                vector.insert(majkey.clone(), self.smoracle.ring().identity_multiplicative.clone());
+                vector
+            })
+            .collect()
+    }
+}
+
+
+// ------------------------------------------------------------------------------------------------
+// HELPER FUNCTIONS
+// ------------------------------------------------------------------------------------------------
+
+
+/// Simplify the entries of a hash map, and drop those that are zero
+fn nonzero_entries<Key: Hash + Eq, SnzVal: RingElement>( ringmetadata: &RingMetadata<SnzVal>, hash: HashMap<Key, SnzVal> ) -> HashMap<Key, SnzVal> {
+    hash.into_iter()
+        .map(|(key, val)| (key, ringmetadata.simplify(&val)))
+        .filter(|(_, val)| !ringmetadata.is_0(val))
+        .collect()
+}
+
+/// Convert a hash map to an iterator that runs over (simplified, nonzero) entries in ascending order of keys
+fn sorted_itr<'b, Key, SnzVal>( ringmetadata: &RingMetadata<SnzVal>, hash: HashMap<Key, SnzVal> ) -> Box<dyn Iterator<Item=(Key, SnzVal)> + 'b> where
+Key: Ord + 'b,
+SnzVal: RingElement + 'b
+{
+    let mut vec: Vec<(Key, SnzVal)> = hash.into_iter()
+        .map(|(key, val)| (key, ringmetadata.simplify(&val)))
+        .filter(|(_, val)| !ringmetadata.is_0(val))
+        .collect();
+    vec.sort_by(|a, b| a.0.cmp(&b.0));
+    Box::new(vec.into_iter())
+}
+
+
+// ------------------------------------------------------------------------------------------------
+// ORACLES
+// ------------------------------------------------------------------------------------------------
+
+
+/// A matrix oracle for the row operation matrix *T* (or its inverse) in a U-match decomposition.
+pub struct UMatchCobRow<'b, 'a, MajKey, MinKey, SnzVal: Clone, Matrix, RowOper> {
+    umatch: &'b UMatch<'a, MajKey, MinKey, SnzVal, Matrix, RowOper>,
+    invert: bool
+}
+
+// See matrix.rs file for specific definition of SmOracle trait
+impl<'b, 'a, MajKey, MinKey, SnzVal, Matrix, RowOper> SmOracle<MajKey, MajKey, SnzVal> for UMatchCobRow<'b, 'a, MajKey, MinKey, SnzVal, Matrix, RowOper> where
+MajKey: PartialEq + Eq + Hash + Clone + Ord + Debug,
+MinKey: PartialEq + Eq + Hash + Clone + Ord + Debug,
+SnzVal: RingElement + Debug,
+Matrix: SmOracle<MajKey, MinKey, SnzVal>,
+RowOper: SmOracle<usize, usize, SnzVal>
+{
+    fn ring( &self ) -> &RingMetadata<SnzVal> { self.umatch.ring() }
+
+    fn maj_dim( &self ) -> MajorDimension { MajorDimension::Row }
+
+    fn maj_itr( &self, majkey: &MajKey ) -> Box<dyn Iterator<Item=(MajKey, SnzVal)> + '_> {
+        if self.invert { sorted_itr(self.umatch.ring(), self.umatch.tinv_row(majkey)) }
+        else { sorted_itr(self.umatch.ring(), self.umatch.t_row(majkey)) }
     }
 
+    fn min_itr( &self, minkey: &MajKey ) -> Box<dyn Iterator<Item=(MajKey, SnzVal)> + '_> {
+        if self.invert { sorted_itr(self.umatch.ring(), self.umatch.tinv_col(minkey)) }
+        else { sorted_itr(self.umatch.ring(), self.umatch.t_col(minkey)) }
+    }
+
+    fn maj_itr_sorted( &self ) -> bool { true }
+
+    fn min_itr_sorted( &self ) -> bool { true }
+}
+
+/// A matrix oracle for the column operation matrix *S* (or its inverse) in a U-match decomposition.
+pub struct UMatchCobCol<'b, 'a, MajKey, MinKey, SnzVal: Clone, Matrix, RowOper> {
+    umatch: &'b UMatch<'a, MajKey, MinKey, SnzVal, Matrix, RowOper>,
+    invert: bool
+}
+
+// See matrix.rs file for specific definition of SmOracle trait
+impl<'b, 'a, MajKey, MinKey, SnzVal, Matrix, RowOper> SmOracle<MinKey, MinKey, SnzVal> for UMatchCobCol<'b, 'a, MajKey, MinKey, SnzVal, Matrix, RowOper> where
+MajKey: PartialEq + Eq + Hash + Clone + Ord + Debug,
+MinKey: PartialEq + Eq + Hash + Clone + Ord + Debug,
+SnzVal: RingElement + Debug,
+Matrix: SmOracle<MajKey, MinKey, SnzVal>,
+RowOper: SmOracle<usize, usize, SnzVal>
+{
+    fn ring( &self ) -> &RingMetadata<SnzVal> { self.umatch.ring() }
+
+    fn maj_dim( &self ) -> MajorDimension { MajorDimension::Row }
+
+    fn maj_itr( &self, majkey: &MinKey ) -> Box<dyn Iterator<Item=(MinKey, SnzVal)> + '_> {
+        if self.invert { sorted_itr(self.umatch.ring(), self.umatch.sinv_row(majkey)) }
+        else { sorted_itr(self.umatch.ring(), self.umatch.s_row(majkey)) }
+    }
+
+    fn min_itr( &self, minkey: &MinKey ) -> Box<dyn Iterator<Item=(MinKey, SnzVal)> + '_> {
+        if self.invert { sorted_itr(self.umatch.ring(), self.umatch.sinv_col(minkey)) }
+        else { sorted_itr(self.umatch.ring(), self.umatch.s_col(minkey)) }
+    }
+
+    fn maj_itr_sorted( &self ) -> bool { true }
+
+    fn min_itr_sorted( &self ) -> bool { true }
+}
+
+/// A matrix oracle for the matching matrix *M* in a U-match decomposition.
+pub struct UMatchMatching<'b, 'a, MajKey, MinKey, SnzVal: Clone, Matrix, RowOper> {
+    umatch: &'b UMatch<'a, MajKey, MinKey, SnzVal, Matrix, RowOper>
+}
+
+// See matrix.rs file for specific definition of SmOracle trait
+impl<'b, 'a, MajKey, MinKey, SnzVal, Matrix, RowOper> SmOracle<MajKey, MinKey, SnzVal> for UMatchMatching<'b, 'a, MajKey, MinKey, SnzVal, Matrix, RowOper> where
+MajKey: PartialEq + Eq + Hash + Clone + Ord + Debug,
+MinKey: PartialEq + Eq + Hash + Clone + Ord + Debug,
+SnzVal: RingElement + Debug,
+Matrix: SmOracle<MajKey, MinKey, SnzVal>,
+RowOper: SmOracle<usize, usize, SnzVal>
+{
+    fn ring( &self ) -> &RingMetadata<SnzVal> { self.umatch.ring() }
+
+    fn maj_dim( &self ) -> MajorDimension { MajorDimension::Row }
+
+    fn maj_itr( &self, majkey: &MajKey ) -> Box<dyn Iterator<Item=(MinKey, SnzVal)> + '_> {
+        let indexing = self.umatch.pivot_bijections;
+        match indexing.majkey_2_index.get(majkey) {
+            Some(index) => Box::new(std::iter::once((indexing.index_2_minkey[*index].clone(), self.umatch.pivot_values[*index].clone()))),
+            None => Box::new(std::iter::empty())
+        }
+    }
+
+    fn min_itr( &self, minkey: &MinKey ) -> Box<dyn Iterator<Item=(MajKey, SnzVal)> + '_> {
+        let indexing = self.umatch.pivot_bijections;
+        match indexing.minkey_2_index.get(minkey) {
+            Some(index) => Box::new(std::iter::once((indexing.index_2_majkey[*index].clone(), self.umatch.pivot_values[*index].clone()))),
+            None => Box::new(std::iter::empty())
+        }
+    }
+
+    fn countsnz( &self ) -> Option<usize> {
+        Some(self.umatch.pivot_values.len())
+    }
+
+    fn maj_itr_sorted( &self ) -> bool { true }
+
+    fn min_itr_sorted( &self ) -> bool { true }
+}
+
+/// A matrix oracle for a generalized inverse *D<sup>+</sup> = S M<sup>+</sup> T<sup>-1</sup>* of the factored matrix; see [`generalized_inverse`](UMatch::generalized_inverse).
+pub struct UMatchGeneralizedInverse<'b, 'a, MajKey, MinKey, SnzVal: Clone, Matrix, RowOper> {
+    umatch: &'b UMatch<'a, MajKey, MinKey, SnzVal, Matrix, RowOper>
+}
+
+// See matrix.rs file for specific definition of SmOracle trait
+impl<'b, 'a, MajKey, MinKey, SnzVal, Matrix, RowOper> SmOracle<MinKey, MajKey, SnzVal> for UMatchGeneralizedInverse<'b, 'a, MajKey, MinKey, SnzVal, Matrix, RowOper> where
+MajKey: PartialEq + Eq + Hash + Clone + Ord + Debug,
+MinKey: PartialEq + Eq + Hash + Clone + Ord + Debug,
+SnzVal: RingElement + Debug,
+Matrix: SmOracle<MajKey, MinKey, SnzVal>,
+RowOper: SmOracle<usize, usize, SnzVal>
+{
+    fn ring( &self ) -> &RingMetadata<SnzVal> { self.umatch.ring() }
+
+    fn maj_dim( &self ) -> MajorDimension { MajorDimension::Row }
+
+    // row `majkey` of S, times M^+, times T^{-1}
+    fn maj_itr( &self, majkey: &MinKey ) -> Box<dyn Iterator<Item=(MajKey, SnzVal)> + '_> {
+        let umatch = self.umatch;
+        let mut output = HashMap::new();
+        for (key, val) in umatch.matching_pseudoinverse_transpose(umatch.s_row(majkey)) {
+            let mut row = umatch.tinv_row(&key);
+            add_assign_hash(umatch.ring(), &mut output, &mut row, &val);
+        }
+        sorted_itr(umatch.ring(), output)
+    }
+
+    // S, times M^+, times column `minkey` of T^{-1}
+    fn min_itr( &self, minkey: &MajKey ) -> Box<dyn Iterator<Item=(MinKey, SnzVal)> + '_> {
+        let umatch = self.umatch;
+        let mut output = HashMap::new();
+        for (key, val) in umatch.matching_pseudoinverse(umatch.tinv_col(minkey)) {
+            let mut column = umatch.s_col(&key);
+            add_assign_hash(umatch.ring(), &mut output, &mut column, &val);
+        }
+        sorted_itr(umatch.ring(), output)
+    }
 
+    fn maj_itr_sorted( &self ) -> bool { true }
+
+    fn min_itr_sorted( &self ) -> bool { true }
 }
 
-/// A matrix oracle for the change of basis matrices in a U-match decomposition.
-struct UMatchCob<MajKey, MinKey, SnzVal> {
-    umatch: &UMatch<MajKey, MinKey, SnzVal>,
-    which_side: MajorDimension
+/// A matrix oracle that remembers the minor fields of another oracle; see [`solve_batch`](UMatch::solve_batch).
+///
+/// Each field is computed once, and stored behind an `Rc`, so a cache hit clones a pointer rather than the field; entries
+/// are cloned one at a time as the iterator runs.  The cache is a `RefCell`, so this oracle is not `Sync`.
+struct MinorFieldCache<Key, SnzVal, Matrix> {
+    matrix: Matrix,
+    fields: RefCell<HashMap<Key, SharedField<Key, SnzVal>>>
 }
 
-impl SmOracle < MajKey, MinKey, SnzVal > for UMatchCob {
-    
-    /// to fill in
-    
+/// A cached field of a [`MinorFieldCache`](MinorFieldCache), as a list of entries in the order the underlying oracle returns them
+type SharedField<Key, SnzVal> = Rc<Vec<(Key, SnzVal)>>;
+
+impl<Key, SnzVal, Matrix> MinorFieldCache<Key, SnzVal, Matrix> {
+    fn new( matrix: Matrix ) -> MinorFieldCache<Key, SnzVal, Matrix> {
+        MinorFieldCache { matrix, fields: RefCell::new(HashMap::new()) }
+    }
 }
 
+// See matrix.rs file for specific definition of SmOracle trait
+impl<Key, SnzVal, Matrix> SmOracle<Key, Key, SnzVal> for MinorFieldCache<Key, SnzVal, Matrix> where
+Key: PartialEq + Eq + Hash + Clone,
+SnzVal: Clone,
+Matrix: SmOracle<Key, Key, SnzVal>
+{
+    fn ring( &self ) -> &RingMetadata<SnzVal> { self.matrix.ring() }
+
+    fn maj_dim( &self ) -> MajorDimension { self.matrix.maj_dim() }
+
+    fn maj_itr( &self, majkey: &Key ) -> Box<dyn Iterator<Item=(Key, SnzVal)> + '_> {
+        self.matrix.maj_itr(majkey)
+    }
+
+    fn min_itr( &self, minkey: &Key ) -> Box<dyn Iterator<Item=(Key, SnzVal)> + '_> {
+        let cached = self.fields.borrow().get(minkey).cloned();
+        let field = match cached {
+            Some(field) => field,
+            None => {
+                let field = Rc::new(self.matrix.min_itr(minkey).collect::<Vec<_>>());
+                self.fields.borrow_mut().insert(minkey.clone(), Rc::clone(&field));
+                field
+            }
+        };
+        Box::new((0..field.len()).map(move |ii| field[ii].clone()))
+    }
+
+    fn maj_itr_sorted( &self ) -> bool { self.matrix.maj_itr_sorted() }
+
+    fn min_itr_sorted( &self ) -> bool { self.matrix.min_itr_sorted() }
+}